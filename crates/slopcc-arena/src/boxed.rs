@@ -1,5 +1,10 @@
 use std::ops::Deref;
 
+/// A `&'static T` handed out by an arena.
+///
+/// Equality, ordering and hashing go through the reference to the value, so
+/// two boxes from separate allocations are equal when their values are.
+/// Compare the references with `std::ptr::eq` for identity.
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
 pub struct ArenaBox<T: 'static>(&'static T);
 
@@ -15,6 +20,12 @@ impl<T: 'static> ArenaBox<T> {
   }
 }
 
+impl<T: PartialEq + 'static> PartialEq<T> for ArenaBox<T> {
+  fn eq(&self, other: &T) -> bool {
+    self.0 == other
+  }
+}
+
 impl<T: 'static> Deref for ArenaBox<T> {
   type Target = T;
 
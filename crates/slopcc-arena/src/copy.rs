@@ -0,0 +1,41 @@
+use crate::Arena;
+
+/// Borrowed data that can be copied into an [`Arena`], so
+/// `value.arena_clone(&arena)` works the same for strings and slices.
+pub trait ArenaCopy {
+  fn arena_clone(&self, arena: &Arena) -> &'static Self;
+}
+
+impl ArenaCopy for str {
+  fn arena_clone(&self, arena: &Arena) -> &'static Self {
+    arena.alloc_str(self)
+  }
+}
+
+impl<T: Copy> ArenaCopy for [T] {
+  fn arena_clone(&self, arena: &Arena) -> &'static Self {
+    arena.alloc_slice(self)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::ArenaCopy;
+  use crate::Arena;
+
+  #[test]
+  fn copies_str_and_slices_out_of_temporaries() {
+    let arena = Arena::new();
+    let mut text = String::from("name");
+    let mut values = vec![1u32, 2, 3];
+
+    let text_copy = text.as_str().arena_clone(&arena);
+    let values_copy = values.as_slice().arena_clone(&arena);
+    text.push_str("-changed");
+    values[0] = 9;
+
+    assert_eq!(text_copy, "name");
+    assert_eq!(values_copy, &[1, 2, 3]);
+    assert!(!std::ptr::eq(values_copy, values.as_slice()));
+  }
+}
@@ -0,0 +1,122 @@
+use std::{
+  collections::HashMap,
+  num::NonZeroU32,
+};
+
+use crate::Arena;
+
+/// Handle to a string in an [`Interner`]. Equal strings from the same
+/// interner get the same symbol, so comparing symbols is O(1). Symbols
+/// are never zero, so `Option<Symbol>` is as small as `Symbol`.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct Symbol(NonZeroU32);
+
+impl Symbol {
+  /// The symbol's number: 1 for the first string interned, and so on.
+  #[must_use]
+  pub fn as_u32(self) -> u32 {
+    self.0.get()
+  }
+}
+
+/// Arena-backed string table. Each distinct string is copied into the arena
+/// once and numbered in insertion order.
+#[derive(Debug, Default)]
+pub struct Interner {
+  arena: Arena,
+  symbols: HashMap<&'static str, Symbol>,
+  strings: Vec<&'static str>,
+}
+
+impl Interner {
+  #[must_use]
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// An interner that copies its strings into `arena`.
+  #[must_use]
+  pub fn with_arena(arena: Arena) -> Self {
+    Self {
+      arena,
+      symbols: HashMap::new(),
+      strings: Vec::new(),
+    }
+  }
+
+  /// The symbol for `s`, copying it into the arena on first sight.
+  pub fn intern(&mut self, s: &str) -> Symbol {
+    if let Some(&symbol) = self.symbols.get(s) {
+      return symbol;
+    }
+    let raw = u32::try_from(self.strings.len() + 1)
+      .ok()
+      .and_then(NonZeroU32::new)
+      .expect("too many interned strings");
+    let symbol = Symbol(raw);
+    let copy = self.arena.alloc_str(s);
+    self.symbols.insert(copy, symbol);
+    self.strings.push(copy);
+    symbol
+  }
+
+  /// The symbol for `s` if it has been interned.
+  #[must_use]
+  pub fn get(&self, s: &str) -> Option<Symbol> {
+    self.symbols.get(s).copied()
+  }
+
+  /// The string `symbol` stands for. Panics if it came from another
+  /// interner with more strings.
+  #[must_use]
+  pub fn resolve(&self, symbol: Symbol) -> &'static str {
+    self.strings[symbol.0.get() as usize - 1]
+  }
+
+  #[must_use]
+  pub fn len(&self) -> usize {
+    self.strings.len()
+  }
+
+  #[must_use]
+  pub fn is_empty(&self) -> bool {
+    self.strings.is_empty()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{
+    Interner,
+    Symbol,
+  };
+
+  #[test]
+  fn equal_strings_share_a_symbol() {
+    let mut interner = Interner::new();
+    let foo = interner.intern("foo");
+    let bar = interner.intern("bar");
+    assert_eq!(interner.intern(&String::from("foo")), foo);
+    assert_ne!(foo, bar);
+    assert_eq!(interner.resolve(foo), "foo");
+    assert_eq!(interner.resolve(bar), "bar");
+    assert_eq!(interner.len(), 2);
+  }
+
+  #[test]
+  fn symbols_count_from_one_and_fill_the_option_niche() {
+    let mut interner = Interner::new();
+    assert_eq!(interner.intern("a").as_u32(), 1);
+    assert_eq!(interner.intern("b").as_u32(), 2);
+    assert_eq!(std::mem::size_of::<Option<Symbol>>(), std::mem::size_of::<u32>());
+  }
+
+  #[test]
+  fn get_does_not_intern() {
+    let mut interner = Interner::new();
+    assert_eq!(interner.get("x"), None);
+    assert!(interner.is_empty());
+    let x = interner.intern("x");
+    assert_eq!(interner.get("x"), Some(x));
+  }
+}
@@ -1,5 +1,7 @@
 use std::{
   alloc::Layout,
+  collections::HashSet,
+  fmt,
   mem::{
     ManuallyDrop,
     MaybeUninit,
@@ -14,10 +16,16 @@ use std::{
 use crate::boxed::ArenaBox;
 
 pub mod boxed;
+pub mod copy;
+pub mod intern;
 pub mod prelude;
 
 const DEFAULT_CHUNK_SIZE: usize = 8 * 1024;
 
+/// Largest chunk [`ChunkGrowth::Doubling`] grows to on its own; a larger
+/// initial size is kept as is.
+const MAX_DOUBLED_CHUNK_SIZE: usize = 1024 * 1024;
+
 struct Chunk {
   storage: NonNull<MaybeUninit<u8>>,
   capacity: usize,
@@ -26,16 +34,28 @@ struct Chunk {
 
 impl Chunk {
   fn new(capacity: usize) -> Self {
-    let layout = Layout::array::<u8>(capacity).expect("chunk layout overflow");
+    Self::try_new(capacity).unwrap_or_else(|error| match error {
+      ArenaError::OutOfMemory { .. } => {
+        std::alloc::handle_alloc_error(Layout::array::<u8>(capacity).unwrap())
+      }
+      ArenaError::TooLarge { .. } => panic!("chunk layout overflow"),
+    })
+  }
+
+  fn try_new(capacity: usize) -> Result<Self, ArenaError> {
+    let layout = Layout::array::<u8>(capacity).map_err(|_| ArenaError::TooLarge {
+      size: capacity,
+      chunk_size: capacity,
+    })?;
     // SAFETY: layout is non-zero size (capacity > 0, enforced by Arena constructors)
     let ptr = unsafe { std::alloc::alloc(layout) };
     let storage = NonNull::new(ptr.cast::<MaybeUninit<u8>>())
-      .unwrap_or_else(|| std::alloc::handle_alloc_error(layout));
-    Self {
+      .ok_or(ArenaError::OutOfMemory { size: capacity })?;
+    Ok(Self {
       storage,
       capacity,
       cursor: 0,
-    }
+    })
   }
 
   fn try_alloc(&mut self, layout: Layout) -> Option<NonNull<u8>> {
@@ -65,12 +85,139 @@ impl Drop for Chunk {
 struct ArenaInner {
   chunks: Vec<Chunk>,
   chunk_size: usize,
+  growth: ChunkGrowth,
+  /// Strings handed out by `alloc_str_interned`.
+  interned: HashSet<&'static str>,
+}
+
+impl ArenaInner {
+  fn alloc_raw(&mut self, layout: Layout) -> NonNull<u8> {
+    self.try_alloc_raw(layout).unwrap_or_else(|error| panic!("{error}"))
+  }
+
+  fn try_alloc_raw(&mut self, layout: Layout) -> Result<NonNull<u8>, ArenaError> {
+    if let Some(ptr) = self.chunks.last_mut().unwrap().try_alloc(layout) {
+      return Ok(ptr);
+    }
+
+    let capacity = self.next_chunk_size();
+    if layout.size() > capacity {
+      return Err(ArenaError::TooLarge {
+        size: layout.size(),
+        chunk_size: capacity,
+      });
+    }
+
+    self.chunks.push(Chunk::try_new(capacity)?);
+    Ok(
+      self
+        .chunks
+        .last_mut()
+        .unwrap()
+        .try_alloc(layout)
+        .expect("fresh chunk must fit allocation within chunk_size"),
+    )
+  }
+
+  fn next_chunk_size(&self) -> usize {
+    match self.growth {
+      ChunkGrowth::Fixed => self.chunk_size,
+      ChunkGrowth::Doubling => {
+        let last = self.chunks.last().map_or(self.chunk_size, |chunk| chunk.capacity);
+        last.saturating_mul(2).min(MAX_DOUBLED_CHUNK_SIZE).max(self.chunk_size)
+      }
+    }
+  }
+}
+
+/// Why a fallible allocation such as [`Arena::try_alloc`] failed. The arena
+/// is unchanged and stays usable.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[non_exhaustive]
+pub enum ArenaError {
+  /// The value needs more bytes than the next chunk holds.
+  TooLarge { size: usize, chunk_size: usize },
+  /// The global allocator could not provide a new chunk.
+  OutOfMemory { size: usize },
+}
+
+impl fmt::Display for ArenaError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::TooLarge { size, chunk_size } => write!(
+        f,
+        "allocation of {size} bytes exceeds chunk size of {chunk_size} bytes"
+      ),
+      Self::OutOfMemory { size } => write!(f, "failed to allocate a {size}-byte chunk"),
+    }
+  }
+}
+
+impl std::error::Error for ArenaError {}
+
+/// How an arena sizes the chunks it adds once the first one fills up.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub enum ChunkGrowth {
+  /// Every chunk has the initial size.
+  #[default]
+  Fixed,
+  /// Each chunk is twice the previous one, up to 1 MiB, so long passes
+  /// make fewer system allocations.
+  Doubling,
+}
+
+/// Configures an [`Arena`]; see [`Arena::builder`].
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct ArenaBuilder {
+  chunk_size: usize,
+  growth: ChunkGrowth,
+}
+
+impl ArenaBuilder {
+  /// Size of the first chunk, and of every chunk under
+  /// [`ChunkGrowth::Fixed`].
+  #[must_use]
+  pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+    self.chunk_size = chunk_size;
+    self
+  }
+
+  #[must_use]
+  pub fn growth(mut self, growth: ChunkGrowth) -> Self {
+    self.growth = growth;
+    self
+  }
+
+  #[must_use]
+  pub fn build(self) -> Arena {
+    assert!(self.chunk_size > 0, "chunk size must be positive");
+    Arena {
+      inner: Mutex::new(ArenaInner {
+        chunks: vec![Chunk::new(self.chunk_size)],
+        chunk_size: self.chunk_size,
+        growth: self.growth,
+        interned: HashSet::new(),
+      }),
+    }
+  }
 }
 
 pub struct Arena {
   inner: Mutex<ArenaInner>,
 }
 
+/// Snapshot of an arena's memory use, in bytes.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct ArenaStats {
+  pub chunks: usize,
+  /// Capacity of every chunk allocated so far.
+  pub reserved: usize,
+  /// Bytes handed out, including alignment padding.
+  pub used: usize,
+  /// The initial chunk size the arena was built with.
+  pub chunk_size: usize,
+}
+
 // SAFETY: All access to ArenaInner goes through the Mutex.
 // Raw pointers in Chunk are exclusively owned by the Arena.
 unsafe impl Send for Arena {}
@@ -84,26 +231,37 @@ impl Arena {
 
   #[must_use]
   pub fn with_chunk_size(chunk_size: usize) -> Self {
-    assert!(chunk_size > 0, "chunk size must be positive");
-    Self {
-      inner: Mutex::new(ArenaInner {
-        chunks: vec![Chunk::new(chunk_size)],
-        chunk_size,
-      }),
+    Self::builder().chunk_size(chunk_size).build()
+  }
+
+  /// Starts from the defaults: 8 KiB chunks, [`ChunkGrowth::Fixed`].
+  #[must_use]
+  pub fn builder() -> ArenaBuilder {
+    ArenaBuilder {
+      chunk_size: DEFAULT_CHUNK_SIZE,
+      growth: ChunkGrowth::Fixed,
     }
   }
 
+  /// Panics if `T` does not fit in a chunk; see [`Arena::try_alloc`].
   pub fn alloc<T>(&self, value: T) -> &'static T {
+    self.try_alloc(value).unwrap_or_else(|error| panic!("{error}"))
+  }
+
+  /// Like [`Arena::alloc`], but returns an error instead of panicking when
+  /// `T` is larger than a chunk or a new chunk cannot be allocated. On
+  /// error `value` is dropped.
+  pub fn try_alloc<T>(&self, value: T) -> Result<&'static T, ArenaError> {
     let layout = Layout::new::<T>();
 
     if layout.size() == 0 {
       std::mem::forget(value);
       // SAFETY: ZST needs no actual memory. NonNull::dangling() provides a
       // validly-aligned, non-null pointer that will never be read from.
-      return unsafe { &*NonNull::<T>::dangling().as_ptr() };
+      return Ok(unsafe { &*NonNull::<T>::dangling().as_ptr() });
     }
 
-    let ptr = self.alloc_raw(layout);
+    let ptr = self.lock().try_alloc_raw(layout)?;
     let value = ManuallyDrop::new(value);
 
     // SAFETY: ptr is valid, aligned for T, and exclusively owned by this arena.
@@ -112,7 +270,7 @@ impl Arena {
     // on drop without running destructors on stored values.
     unsafe {
       ptr::copy_nonoverlapping(&*value as *const T, ptr.as_ptr().cast::<T>(), 1);
-      &*ptr.as_ptr().cast::<T>()
+      Ok(&*ptr.as_ptr().cast::<T>())
     }
   }
 
@@ -130,6 +288,29 @@ impl Arena {
     unsafe { std::str::from_utf8_unchecked(bytes) }
   }
 
+  /// Like [`Arena::alloc_str`], but equal strings share one copy: the first
+  /// call allocates and later calls return the same reference.
+  pub fn alloc_str_interned(&self, s: &str) -> &'static str {
+    if s.is_empty() {
+      return "";
+    }
+    let mut inner = self.lock();
+    if let Some(&existing) = inner.interned.get(s) {
+      return existing;
+    }
+
+    let layout = Layout::array::<u8>(s.len()).expect("slice layout overflow");
+    let ptr = inner.alloc_raw(layout).as_ptr();
+    // SAFETY: ptr is valid for s.len() bytes and exclusively owned by this
+    // arena; the bytes are copied verbatim from valid UTF-8.
+    let copy = unsafe {
+      ptr::copy_nonoverlapping(s.as_ptr(), ptr, s.len());
+      std::str::from_utf8_unchecked(std::slice::from_raw_parts(ptr, s.len()))
+    };
+    inner.interned.insert(copy);
+    copy
+  }
+
   pub fn alloc_slice<T: Copy>(&self, slice: &[T]) -> &'static [T] {
     if slice.is_empty() {
       return &[];
@@ -146,28 +327,73 @@ impl Arena {
     }
   }
 
-  fn alloc_raw(&self, layout: Layout) -> NonNull<u8> {
-    let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+  /// Uninitialized storage for `len` values, for slices filled out of order.
+  /// Write every element before reading any of it back (for example by
+  /// casting the `*mut [MaybeUninit<T>]` to `*const [T]`). As with every
+  /// arena value, the elements are never dropped. Zero-sized `T` and `len
+  /// == 0` take no arena memory.
+  #[allow(clippy::mut_from_ref)]
+  pub fn alloc_uninit_slice<T>(&self, len: usize) -> &'static mut [MaybeUninit<T>] {
+    let layout = Layout::array::<T>(len).expect("slice layout overflow");
+    let ptr = if layout.size() == 0 {
+      NonNull::<MaybeUninit<T>>::dangling()
+    } else {
+      self.alloc_raw(layout).cast::<MaybeUninit<T>>()
+    };
 
-    assert!(
-      layout.size() <= inner.chunk_size,
-      "allocation of {} bytes exceeds chunk size of {} bytes",
-      layout.size(),
-      inner.chunk_size,
-    );
+    // SAFETY: ptr is aligned for T and valid for len elements (or T/len is
+    // zero-sized), freshly allocated so no other reference aliases it, and
+    // MaybeUninit<T> needs no initialization.
+    unsafe { std::slice::from_raw_parts_mut(ptr.as_ptr(), len) }
+  }
+
+  /// Like [`Arena::alloc_slice`], but keeps the length in the type.
+  pub fn alloc_array<T: Copy, const N: usize>(&self, array: [T; N]) -> &'static [T; N] {
+    self.alloc(array)
+  }
+
+  /// Copies `slices` end to end into one allocation.
+  pub fn alloc_slice_concat<T: Copy>(&self, slices: &[&[T]]) -> &'static [T] {
+    let len = slices
+      .iter()
+      .try_fold(0usize, |len, slice| len.checked_add(slice.len()))
+      .expect("slice layout overflow");
+    if len == 0 {
+      return &[];
+    }
 
-    if let Some(ptr) = inner.chunks.last_mut().unwrap().try_alloc(layout) {
-      return ptr;
+    let layout = Layout::array::<T>(len).expect("slice layout overflow");
+    let ptr = self.alloc_raw(layout).as_ptr().cast::<T>();
+
+    // SAFETY: ptr is valid, aligned for T, has room for len elements, and
+    // the slices' lengths sum to len. T: Copy so no drop concerns.
+    unsafe {
+      let mut offset = 0;
+      for slice in slices {
+        ptr::copy_nonoverlapping(slice.as_ptr(), ptr.add(offset), slice.len());
+        offset += slice.len();
+      }
+      std::slice::from_raw_parts(ptr, len)
     }
+  }
 
-    let chunk_size = inner.chunk_size;
-    inner.chunks.push(Chunk::new(chunk_size));
-    inner
-      .chunks
-      .last_mut()
-      .unwrap()
-      .try_alloc(layout)
-      .expect("fresh chunk must fit allocation within chunk_size")
+  #[must_use]
+  pub fn stats(&self) -> ArenaStats {
+    let inner = self.lock();
+    ArenaStats {
+      chunks: inner.chunks.len(),
+      reserved: inner.chunks.iter().map(|chunk| chunk.capacity).sum(),
+      used: inner.chunks.iter().map(|chunk| chunk.cursor).sum(),
+      chunk_size: inner.chunk_size,
+    }
+  }
+
+  fn alloc_raw(&self, layout: Layout) -> NonNull<u8> {
+    self.lock().alloc_raw(layout)
+  }
+
+  fn lock(&self) -> std::sync::MutexGuard<'_, ArenaInner> {
+    self.inner.lock().unwrap_or_else(|e| e.into_inner())
   }
 }
 
@@ -177,6 +403,18 @@ impl Default for Arena {
   }
 }
 
+impl fmt::Debug for Arena {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let stats = self.stats();
+    f.debug_struct("Arena")
+      .field("chunks", &stats.chunks)
+      .field("reserved", &stats.reserved)
+      .field("used", &stats.used)
+      .field("chunk_size", &stats.chunk_size)
+      .finish()
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -224,6 +462,17 @@ mod tests {
     assert_eq!(s, "");
   }
 
+  #[test]
+  fn interned_strings_share_one_copy() {
+    let arena = Arena::new();
+    let a = arena.alloc_str_interned("x");
+    let b = arena.alloc_str_interned(&String::from("x"));
+    assert!(ptr::eq(a, b));
+    assert!(!ptr::eq(a, arena.alloc_str_interned("y")));
+    assert!(!ptr::eq(arena.alloc_str("x"), arena.alloc_str("x")));
+    assert_eq!(arena.stats().used, 4);
+  }
+
   #[test]
   fn alloc_slice_roundtrip() {
     let arena = Arena::new();
@@ -238,6 +487,91 @@ mod tests {
     assert!(s.is_empty());
   }
 
+  #[test]
+  fn alloc_array_keeps_its_length() {
+    let arena = Arena::new();
+    let table: &[u8; 4] = arena.alloc_array([1, 2, 3, 4]);
+    assert_eq!(table[3], 4);
+    assert_eq!(table.len(), 4);
+    assert!(arena.alloc_array::<u8, 0>([]).is_empty());
+  }
+
+  #[test]
+  fn alloc_slice_concat_joins_in_one_allocation() {
+    let arena = Arena::with_chunk_size(64);
+    let before = arena.inner.lock().unwrap().chunks[0].cursor;
+    let s = arena.alloc_slice_concat(&[&[1u32, 2][..], &[], &[3, 4, 5]]);
+    let after = arena.inner.lock().unwrap().chunks[0].cursor;
+    assert_eq!(s, &[1, 2, 3, 4, 5]);
+    assert_eq!(after - before, 5 * std::mem::size_of::<u32>());
+  }
+
+  #[test]
+  fn alloc_uninit_slice_fills_out_of_order() {
+    let arena = Arena::with_chunk_size(64);
+    let slots = arena.alloc_uninit_slice::<u32>(4);
+    for index in [2, 0, 3, 1] {
+      slots[index].write(index as u32 * 10);
+    }
+    // SAFETY: every element was written above.
+    let values = unsafe { &*(slots as *mut [MaybeUninit<u32>] as *const [u32]) };
+    assert_eq!(values, &[0, 10, 20, 30]);
+    assert_eq!(arena.stats().used, 16);
+
+    assert!(arena.alloc_uninit_slice::<u64>(0).is_empty());
+    assert_eq!(arena.alloc_uninit_slice::<()>(5).len(), 5);
+    assert_eq!(arena.stats().used, 16);
+  }
+
+  #[test]
+  fn alloc_slice_concat_of_empty_slices() {
+    let arena = Arena::new();
+    assert!(arena.alloc_slice_concat::<u8>(&[]).is_empty());
+    assert!(arena.alloc_slice_concat::<u8>(&[&[], &[]]).is_empty());
+  }
+
+  #[test]
+  fn stats_track_chunks_and_used_bytes() {
+    let arena = Arena::with_chunk_size(64);
+    arena.alloc(1u64);
+    arena.alloc_slice(&[0u8; 60]);
+    assert_eq!(
+      arena.stats(),
+      ArenaStats {
+        chunks: 2,
+        reserved: 128,
+        used: 68,
+        chunk_size: 64,
+      }
+    );
+  }
+
+  #[test]
+  fn builder_sets_chunk_size_and_growth() {
+    let arena = Arena::builder().chunk_size(64).build();
+    assert_eq!(arena.stats().chunk_size, 64);
+    assert_eq!(arena.stats().reserved, 64);
+    assert_eq!(Arena::builder().build().stats().chunk_size, Arena::new().stats().chunk_size);
+
+    let arena = Arena::builder().chunk_size(64).growth(ChunkGrowth::Doubling).build();
+    arena.alloc_slice(&[0u8; 60]);
+    arena.alloc_slice(&[0u8; 100]);
+    arena.alloc_slice(&[0u8; 200]);
+    let stats = arena.stats();
+    assert_eq!((stats.chunks, stats.reserved, stats.chunk_size), (3, 64 + 128 + 256, 64));
+  }
+
+  #[test]
+  fn debug_reports_utilization() {
+    let arena = Arena::with_chunk_size(64);
+    arena.alloc(1u32);
+    arena.alloc(2u32);
+    assert_eq!(
+      format!("{arena:?}"),
+      "Arena { chunks: 1, reserved: 64, used: 8, chunk_size: 64 }"
+    );
+  }
+
   #[test]
   fn alloc_zst() {
     let arena = Arena::with_chunk_size(64);
@@ -259,6 +593,29 @@ mod tests {
     }
   }
 
+  #[test]
+  fn try_alloc_returns_the_value() {
+    let arena = Arena::with_chunk_size(64);
+    assert_eq!(arena.try_alloc(7u64).copied(), Ok(7));
+    assert_eq!(arena.try_alloc(()).copied(), Ok(()));
+  }
+
+  #[test]
+  fn try_alloc_reports_too_large_values() {
+    let arena = Arena::with_chunk_size(16);
+    let error = arena.try_alloc([0u8; 17]).unwrap_err();
+    assert_eq!(
+      error,
+      ArenaError::TooLarge {
+        size: 17,
+        chunk_size: 16,
+      }
+    );
+    assert_eq!(error.to_string(), "allocation of 17 bytes exceeds chunk size of 16 bytes");
+    assert_eq!(arena.try_alloc([1u8; 16]).map(|x| x[15]), Ok(1));
+    assert_eq!(arena.stats().chunks, 1);
+  }
+
   #[test]
   #[should_panic(expected = "exceeds chunk size")]
   fn oversized_alloc_panics() {
@@ -335,4 +692,24 @@ mod tests {
     assert_eq!(*value, 77);
     assert_eq!(*value.as_ref(), 77);
   }
+
+  #[test]
+  fn arena_boxes_compare_by_value() {
+    let arena = Arena::new();
+    let a = arena.alloc_box(String::from("x"));
+    let b = arena.alloc_box(String::from("x"));
+    let c = arena.alloc_box(String::from("y"));
+    assert_eq!(a, b);
+    assert!(!std::ptr::eq(&*a, &*b));
+    assert_ne!(a, c);
+    assert!(a < c);
+  }
+
+  #[test]
+  fn arena_box_compares_with_bare_value() {
+    let arena = Arena::new();
+    let value = arena.alloc_box(5u32);
+    assert!(value == 5);
+    assert!(value != 6);
+  }
 }
@@ -1,4 +1,13 @@
 pub use crate::{
   boxed::ArenaBox,
+  copy::ArenaCopy,
+  intern::{
+    Interner,
+    Symbol,
+  },
   Arena,
+  ArenaBuilder,
+  ArenaError,
+  ArenaStats,
+  ChunkGrowth,
 };
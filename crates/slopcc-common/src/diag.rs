@@ -1,3 +1,11 @@
+#[cfg(feature = "alloc")]
+use alloc::{
+  string::String,
+  vec::Vec,
+};
+use core::fmt;
+
+#[cfg(feature = "alloc")]
 use crate::span::Span;
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
@@ -7,18 +15,85 @@ pub enum Severity {
   Note,
 }
 
+impl fmt::Display for Severity {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(match self {
+      Self::Error => "error",
+      Self::Warning => "warning",
+      Self::Note => "note",
+    })
+  }
+}
+
+#[cfg(feature = "alloc")]
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub struct Diagnostic {
   pub severity: Severity,
   pub message: String,
   pub span: Option<Span>,
+  /// Warning option name without the `-W` prefix (`"comment"` for
+  /// `-Wcomment`), used to filter and promote warnings.
+  pub code: Option<&'static str>,
 }
 
+#[cfg(feature = "alloc")]
+impl Diagnostic {
+  #[must_use]
+  pub fn error(message: impl Into<String>, span: Span) -> Self {
+    Self {
+      severity: Severity::Error,
+      message: message.into(),
+      span: Some(span),
+      code: None,
+    }
+  }
+
+  /// An error about the whole run rather than a place in the source, such
+  /// as "no input files"; it renders behind `slopcc:`.
+  #[must_use]
+  pub fn global_error(message: impl Into<String>) -> Self {
+    Self {
+      severity: Severity::Error,
+      message: message.into(),
+      span: None,
+      code: None,
+    }
+  }
+
+  #[must_use]
+  pub fn warning(message: impl Into<String>, span: Span) -> Self {
+    Self {
+      severity: Severity::Warning,
+      message: message.into(),
+      span: Some(span),
+      code: None,
+    }
+  }
+
+  #[must_use]
+  pub fn note(message: impl Into<String>, span: Span) -> Self {
+    Self {
+      severity: Severity::Note,
+      message: message.into(),
+      span: Some(span),
+      code: None,
+    }
+  }
+
+  #[must_use]
+  pub fn with_code(mut self, code: &'static str) -> Self {
+    self.code = Some(code);
+    self
+  }
+}
+
+#[cfg(feature = "alloc")]
 #[derive(Default, Clone, Eq, PartialEq, Debug)]
 pub struct Diagnostics {
   items: Vec<Diagnostic>,
 }
 
+#[cfg(feature = "alloc")]
 impl Diagnostics {
   #[must_use]
   pub fn new() -> Self {
@@ -37,6 +112,15 @@ impl Diagnostics {
       .any(|diagnostic| diagnostic.severity == Severity::Error)
   }
 
+  /// Turns each warning `promote` accepts into an error (`-Werror`).
+  pub fn promote_warnings_to_errors(&mut self, mut promote: impl FnMut(&Diagnostic) -> bool) {
+    for diagnostic in &mut self.items {
+      if diagnostic.severity == Severity::Warning && promote(diagnostic) {
+        diagnostic.severity = Severity::Error;
+      }
+    }
+  }
+
   pub fn iter(&self) -> impl Iterator<Item = &Diagnostic> {
     self.items.iter()
   }
@@ -52,13 +136,64 @@ impl Diagnostics {
   }
 }
 
-#[cfg(test)]
+#[cfg(feature = "alloc")]
+impl<'a> IntoIterator for &'a Diagnostics {
+  type Item = &'a Diagnostic;
+  type IntoIter = core::slice::Iter<'a, Diagnostic>;
+
+  fn into_iter(self) -> Self::IntoIter {
+    self.items.iter()
+  }
+}
+
+#[cfg(feature = "alloc")]
+impl IntoIterator for Diagnostics {
+  type Item = Diagnostic;
+  type IntoIter = alloc::vec::IntoIter<Diagnostic>;
+
+  fn into_iter(self) -> Self::IntoIter {
+    self.items.into_iter()
+  }
+}
+
+/// Appends in order; `diagnostics.extend(other)` merges another phase's
+/// `Diagnostics` since it iterates by value.
+#[cfg(feature = "alloc")]
+impl Extend<Diagnostic> for Diagnostics {
+  fn extend<I: IntoIterator<Item = Diagnostic>>(&mut self, iter: I) {
+    self.items.extend(iter);
+  }
+}
+
+#[cfg(feature = "alloc")]
+impl FromIterator<Diagnostic> for Diagnostics {
+  fn from_iter<I: IntoIterator<Item = Diagnostic>>(iter: I) -> Self {
+    Self {
+      items: iter.into_iter().collect(),
+    }
+  }
+}
+
+#[cfg(all(test, feature = "alloc"))]
 mod tests {
+  use alloc::{
+    string::{
+      String,
+      ToString,
+    },
+    vec,
+    vec::Vec,
+  };
+
   use super::{
     Diagnostic,
     Diagnostics,
     Severity,
   };
+  use crate::span::{
+    FileId,
+    Span,
+  };
 
   #[test]
   fn has_errors_tracks_error_severity() {
@@ -67,6 +202,7 @@ mod tests {
       severity: Severity::Warning,
       message: String::from("warn"),
       span: None,
+      code: None,
     });
     assert!(!diagnostics.has_errors());
 
@@ -74,7 +210,77 @@ mod tests {
       severity: Severity::Error,
       message: String::from("err"),
       span: None,
+      code: None,
     });
     assert!(diagnostics.has_errors());
   }
+
+  #[test]
+  fn promotes_selected_warnings_to_errors() {
+    let span = Span::new(FileId::new_for_tests(0), 0, 1);
+    let mut diagnostics = Diagnostics::new();
+    diagnostics.push(Diagnostic::warning("a", span).with_code("a"));
+    diagnostics.push(Diagnostic::warning("b", span).with_code("b"));
+    assert!(!diagnostics.has_errors());
+
+    diagnostics.promote_warnings_to_errors(|diagnostic| diagnostic.code == Some("b"));
+    let severities: Vec<_> = diagnostics.iter().map(|d| d.severity).collect();
+    assert_eq!(severities, [Severity::Warning, Severity::Error]);
+    assert!(diagnostics.has_errors());
+  }
+
+  #[test]
+  fn collects_into_and_iterates_out_of_diagnostics() {
+    let span = Span::new(FileId::new_for_tests(0), 0, 1);
+    let diagnostics: Diagnostics =
+      vec![Diagnostic::warning("a", span), Diagnostic::error("b", span)].into_iter().collect();
+    assert_eq!(diagnostics.len(), 2);
+
+    let mut messages = Vec::new();
+    for diagnostic in &diagnostics {
+      messages.push(diagnostic.message.as_str());
+    }
+    assert_eq!(messages, ["a", "b"]);
+
+    let owned: Vec<Diagnostic> = diagnostics.into_iter().collect();
+    assert_eq!(owned[1].severity, Severity::Error);
+  }
+
+  #[test]
+  fn extend_merges_sets_in_order() {
+    let span = Span::new(FileId::new_for_tests(0), 0, 1);
+    let mut lexing: Diagnostics = [Diagnostic::warning("a", span)].into_iter().collect();
+    let parsing: Diagnostics =
+      [Diagnostic::error("b", span), Diagnostic::warning("c", span)].into_iter().collect();
+
+    lexing.extend(parsing);
+    let messages: Vec<_> = lexing.iter().map(|d| d.message.as_str()).collect();
+    assert_eq!(messages, ["a", "b", "c"]);
+    assert!(lexing.has_errors());
+  }
+
+  #[test]
+  fn severity_displays_gcc_label() {
+    assert_eq!(Severity::Error.to_string(), "error");
+    assert_eq!(Severity::Warning.to_string(), "warning");
+    assert_eq!(Severity::Note.to_string(), "note");
+  }
+
+  #[test]
+  fn constructors_set_severity_and_span() {
+    let span = Span::new(FileId::new_for_tests(0), 1, 2);
+    let error = Diagnostic::error("bad", span);
+    assert_eq!(error.severity, Severity::Error);
+    assert_eq!(error.message, "bad");
+    assert_eq!(error.span, Some(span));
+    assert_eq!(Diagnostic::warning("meh", span).severity, Severity::Warning);
+    assert_eq!(error.code, None);
+  }
+
+  #[test]
+  fn with_code_tags_the_warning_option() {
+    let span = Span::new(FileId::new_for_tests(0), 0, 1);
+    let warning = Diagnostic::warning("meh", span).with_code("comment");
+    assert_eq!(warning.code, Some("comment"));
+  }
 }
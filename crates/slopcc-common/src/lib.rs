@@ -1,4 +1,15 @@
+//! Shared compiler types. `Span`, `FileId` and `Severity` are `no_std`;
+//! the `alloc` feature adds `Diagnostic`/`Diagnostics` and `std` (the
+//! default) adds source loading and rendering.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
 pub mod diag;
 pub mod prelude;
+#[cfg(feature = "std")]
+pub mod render;
+#[cfg(feature = "std")]
 pub mod source;
 pub mod span;
@@ -1,11 +1,13 @@
+#[cfg(feature = "alloc")]
+pub use crate::diag::{
+  Diagnostic,
+  Diagnostics,
+};
+#[cfg(feature = "std")]
 pub use crate::{
-  diag::{
-    Diagnostic,
-    Diagnostics,
-    Severity,
-  },
+  render::Renderer,
   source::{
-    FileId,
+    Expansion,
     LineCol,
     ResolvedSpan,
     SourceError,
@@ -13,5 +15,11 @@ pub use crate::{
     SourceMap,
     SourceName,
   },
-  span::Span,
+};
+pub use crate::{
+  diag::Severity,
+  span::{
+    FileId,
+    Span,
+  },
 };
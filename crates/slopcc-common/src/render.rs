@@ -0,0 +1,282 @@
+use crate::{
+  diag::{
+    Diagnostic,
+    Severity,
+  },
+  source::SourceMap,
+  span::Span,
+};
+
+const RESET: &str = "\x1b[0m";
+
+/// Stands in for a location on diagnostics that have none.
+const PROGRAM: &str = "slopcc";
+
+/// GCC's bold green for the underline below a source line.
+const CARET_COLOR: &str = "\x1b[1;32m";
+
+/// Spans over more lines than this show only their first and last line.
+const MAX_SNIPPET_LINES: u32 = 4;
+
+/// Longest chain of "in expansion of macro" notes printed, which also stops
+/// a cyclic expansion record from looping.
+const MAX_EXPANSION_NOTES: usize = 64;
+
+/// Formats diagnostics for the terminal, optionally with ANSI colors.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub struct Renderer {
+  color: bool,
+}
+
+impl Renderer {
+  #[must_use]
+  pub fn new(color: bool) -> Self {
+    Self { color }
+  }
+
+  /// `severity: message`, with the severity label colored when enabled.
+  #[must_use]
+  pub fn render(&self, diagnostic: &Diagnostic) -> String {
+    if self.color {
+      format!(
+        "{}{}:{RESET} {}",
+        severity_color(diagnostic.severity),
+        diagnostic.severity,
+        diagnostic.message
+      )
+    } else {
+      format!("{}: {}", diagnostic.severity, diagnostic.message)
+    }
+  }
+
+  /// The full text of `diagnostic`, one line per entry. A located one is
+  /// `path:line:col: severity: message` followed by its [`snippet`] and,
+  /// when its span came from a macro expansion, an "in expansion of macro"
+  /// note at each invocation back to the outermost; a global one (no span,
+  /// or [`Span::DUMMY`]) is `slopcc: severity: message` with no location or
+  /// source lines.
+  ///
+  /// [`snippet`]: Renderer::snippet
+  #[must_use]
+  pub fn report(&self, diagnostic: &Diagnostic, sources: &SourceMap) -> String {
+    let mut out = self.report_one(diagnostic, sources);
+    let mut span = diagnostic.span.filter(|span| !span.is_dummy());
+    for _ in 0..MAX_EXPANSION_NOTES {
+      let Some(expansion) = span.and_then(|span| sources.expansion(span)) else {
+        break;
+      };
+      let message = format!("in expansion of macro '{}'", expansion.macro_name);
+      out.push_str(&self.report_one(&Diagnostic::note(message, expansion.site), sources));
+      span = Some(expansion.site);
+    }
+    out
+  }
+
+  fn report_one(&self, diagnostic: &Diagnostic, sources: &SourceMap) -> String {
+    match diagnostic.span.filter(|span| !span.is_dummy()) {
+      Some(span) => format!(
+        "{} {}\n{}",
+        location(sources, span),
+        self.render(diagnostic),
+        self.snippet(span, sources)
+      ),
+      None => format!("{PROGRAM}: {}\n", self.render(diagnostic)),
+    }
+  }
+
+  /// The source lines under `span`, GCC style: each line behind a
+  /// `line | ` gutter with the covered bytes underlined below it, `^` at
+  /// the start and `~` after. A span across lines is underlined from its
+  /// start to the end of the first line and up to its end on the last;
+  /// past four lines only those two are shown, with `...` between.
+  #[must_use]
+  pub fn snippet(&self, span: Span, sources: &SourceMap) -> String {
+    let file = sources.file(span.file());
+    let len = u32::try_from(file.bytes().len()).unwrap_or(u32::MAX);
+    let start = span.start().min(len);
+    let last = span.end().min(len).saturating_sub(1).max(start);
+    let first = file.line_col(start);
+    let last = file.line_col(last);
+
+    let mut out = String::new();
+    let elide = last.line - first.line + 1 > MAX_SNIPPET_LINES;
+    for line in first.line..=last.line {
+      if elide && line > first.line && line < last.line {
+        if line == first.line + 1 {
+          out.push_str(&format!("{:>5} |\n", "..."));
+        }
+        continue;
+      }
+      let text = file.line_text(line).unwrap_or_default();
+      out.push_str(&format!("{line:>5} | {}\n", String::from_utf8_lossy(text)));
+
+      let from = if line == first.line { first.column as usize } else { 1 };
+      let to = if line == last.line { last.column as usize } else { text.len() };
+      let to = to.min(text.len()).max(if line == first.line { from } else { 0 });
+      if to < from {
+        continue;
+      }
+      let pad: String = text[..(from - 1).min(text.len())]
+        .iter()
+        .map(|&byte| if byte == b'\t' { '\t' } else { ' ' })
+        .collect();
+      let lead = if line == first.line { "^" } else { "~" };
+      let marks = format!("{lead}{}", "~".repeat(to - from));
+      let marks = if self.color { format!("{CARET_COLOR}{marks}{RESET}") } else { marks };
+      out.push_str(&format!("{:>5} | {pad}{marks}\n", ""));
+    }
+    out
+  }
+}
+
+/// GCC's `path:line:col:` prefix for the start of `span`; standard input is
+/// `<stdin>`.
+fn location(sources: &SourceMap, span: Span) -> String {
+  let resolved = sources.resolve_span(span);
+  format!("{}:{}:{}:", resolved.source_name, resolved.line, resolved.column)
+}
+
+/// GCC's default colors: bold red errors, bold magenta warnings, bold cyan
+/// notes.
+fn severity_color(severity: Severity) -> &'static str {
+  match severity {
+    Severity::Error => "\x1b[1;31m",
+    Severity::Warning => "\x1b[1;35m",
+    Severity::Note => "\x1b[1;36m",
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::path::PathBuf;
+
+  use super::{
+    location,
+    Renderer,
+  };
+  use crate::{
+    diag::Diagnostic,
+    source::{
+      FileId,
+      SourceMap,
+    },
+    span::Span,
+  };
+
+  fn warning() -> Diagnostic {
+    Diagnostic::warning("careful", Span::new(FileId::new_for_tests(0), 0, 1))
+  }
+
+  #[test]
+  fn plain_output_has_no_escape_codes() {
+    assert_eq!(Renderer::new(false).render(&warning()), "warning: careful");
+  }
+
+  #[test]
+  fn colored_output_wraps_severity() {
+    assert_eq!(
+      Renderer::new(true).render(&warning()),
+      "\x1b[1;35mwarning:\x1b[0m careful"
+    );
+  }
+
+  #[test]
+  fn location_names_paths_and_stdin() {
+    let mut sources = SourceMap::new();
+    let stdin = sources.add_stdin(b"int x;\n@\n".to_vec());
+    assert_eq!(location(&sources, Span::new(stdin, 7, 8)), "<stdin>:2:1:");
+    let file = sources.add_file(PathBuf::from("dir/a.c"), b"a b".to_vec());
+    assert_eq!(location(&sources, Span::new(file, 2, 3)), "dir/a.c:1:3:");
+  }
+
+  #[test]
+  fn report_locates_and_quotes_spanned_diagnostics() {
+    let mut sources = SourceMap::new();
+    let file = sources.add_file(PathBuf::from("a.c"), b"int @;\n".to_vec());
+    let error = Diagnostic::error("stray '@' in program", Span::new(file, 4, 5));
+    assert_eq!(
+      Renderer::new(false).report(&error, &sources),
+      "a.c:1:5: error: stray '@' in program\n    1 | int @;\n      |     ^\n"
+    );
+  }
+
+  #[test]
+  fn report_prints_global_diagnostics_without_a_location() {
+    let sources = SourceMap::new();
+    let renderer = Renderer::new(false);
+    let error = Diagnostic::global_error("no input files");
+    assert_eq!(error.span, None);
+    assert_eq!(renderer.report(&error, &sources), "slopcc: error: no input files\n");
+    let dummy = Diagnostic::warning("synthesized", Span::DUMMY);
+    assert_eq!(renderer.report(&dummy, &sources), "slopcc: warning: synthesized\n");
+  }
+
+  #[test]
+  fn report_walks_the_macro_expansion_chain() {
+    let mut sources = SourceMap::new();
+    let src = b"#define FOO @\n#define BAR FOO\nBAR;\n";
+    let file = sources.add_file(PathBuf::from("m.c"), src.to_vec());
+    let at = Span::new(file, 12, 13);
+    let foo = Span::new(file, 26, 29);
+    sources.record_expansion(at, "FOO", foo);
+    sources.record_expansion(foo, "BAR", Span::new(file, 30, 33));
+
+    let error = Diagnostic::error("stray '@' in program", at);
+    assert_eq!(
+      Renderer::new(false).report(&error, &sources),
+      concat!(
+        "m.c:1:13: error: stray '@' in program\n",
+        "    1 | #define FOO @\n",
+        "      |             ^\n",
+        "m.c:2:13: note: in expansion of macro 'FOO'\n",
+        "    2 | #define BAR FOO\n",
+        "      |             ^~~\n",
+        "m.c:3:1: note: in expansion of macro 'BAR'\n",
+        "    3 | BAR;\n",
+        "      | ^~~\n",
+      )
+    );
+  }
+
+  #[test]
+  fn snippet_underlines_a_single_line_span() {
+    let mut sources = SourceMap::new();
+    let file = sources.add_stdin(b"int x;\n\tx = @y;\n".to_vec());
+    assert_eq!(
+      Renderer::new(false).snippet(Span::new(file, 12, 14), &sources),
+      "    2 | \tx = @y;\n      | \t    ^~\n"
+    );
+  }
+
+  #[test]
+  fn snippet_brackets_a_multi_line_span() {
+    let mut sources = SourceMap::new();
+    let file = sources.add_stdin(b"int x; /* open\nstill open\n".to_vec());
+    assert_eq!(
+      Renderer::new(false).snippet(Span::new(file, 7, 26), &sources),
+      concat!(
+        "    1 | int x; /* open\n",
+        "      |        ^~~~~~~\n",
+        "    2 | still open\n",
+        "      | ~~~~~~~~~~\n",
+      )
+    );
+  }
+
+  #[test]
+  fn snippet_elides_the_middle_of_long_spans() {
+    let mut sources = SourceMap::new();
+    let file = sources.add_stdin(b"/* a\nb\nc\nd\ne */ f\n".to_vec());
+    let snippet = Renderer::new(false).snippet(Span::new(file, 0, 15), &sources);
+    assert_eq!(
+      snippet,
+      concat!(
+        "    1 | /* a\n",
+        "      | ^~~~\n",
+        "  ... |\n",
+        "    5 | e */ f\n",
+        "      | ~~~~\n",
+      )
+    );
+  }
+}
@@ -1,25 +1,16 @@
-use std::path::{
-  Path,
-  PathBuf,
+use std::{
+  collections::HashMap,
+  fmt,
+  path::{
+    Path,
+    PathBuf,
+  },
+  sync::Arc,
 };
 
+pub use crate::span::FileId;
 use crate::span::Span;
 
-#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
-pub struct FileId(u32);
-
-impl FileId {
-  #[must_use]
-  pub fn as_u32(self) -> u32 {
-    self.0
-  }
-
-  #[must_use]
-  pub fn new_for_tests(raw: u32) -> Self {
-    Self(raw)
-  }
-}
-
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub struct LineCol {
   pub line: u32,
@@ -30,6 +21,21 @@ pub struct LineCol {
 pub enum SourceName<'a> {
   Path(&'a Path),
   Stdin,
+  /// An in-memory buffer with no path, such as `<command-line>`.
+  Virtual(&'a str),
+}
+
+/// The name diagnostics print: the path, `<stdin>`, or the virtual name
+/// (`<built-in>`, `<command-line>`). Paths print as given, so `./foo.c`
+/// keeps its `./` as it does in GCC's messages.
+impl fmt::Display for SourceName<'_> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::Path(path) => write!(f, "{}", path.display()),
+      Self::Stdin => f.write_str("<stdin>"),
+      Self::Virtual(name) => f.write_str(name),
+    }
+  }
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
@@ -40,10 +46,17 @@ pub struct ResolvedSpan<'a> {
   pub length: u32,
 }
 
+/// Where a [`SourceFile`]'s bytes came from.
+enum Origin {
+  Path(PathBuf),
+  Stdin,
+  Virtual(String),
+}
+
 pub struct SourceFile {
   id: FileId,
-  path: Option<PathBuf>,
-  bytes: Box<[u8]>,
+  origin: Origin,
+  bytes: Arc<[u8]>,
   line_starts: Box<[u32]>,
 }
 
@@ -53,9 +66,22 @@ impl SourceFile {
     self.id
   }
 
+  /// The file's path; `None` for stdin and virtual files.
   #[must_use]
   pub fn path(&self) -> Option<&Path> {
-    self.path.as_deref()
+    match &self.origin {
+      Origin::Path(path) => Some(path),
+      Origin::Stdin | Origin::Virtual(_) => None,
+    }
+  }
+
+  #[must_use]
+  pub fn name(&self) -> SourceName<'_> {
+    match &self.origin {
+      Origin::Path(path) => SourceName::Path(path),
+      Origin::Stdin => SourceName::Stdin,
+      Origin::Virtual(name) => SourceName::Virtual(name),
+    }
   }
 
   #[must_use]
@@ -63,6 +89,27 @@ impl SourceFile {
     &self.bytes
   }
 
+  /// A handle to the file's bytes that outlives borrows of the map, so a
+  /// worker thread can keep reading them. Cloning it copies no bytes.
+  #[must_use]
+  pub fn shared_bytes(&self) -> Arc<[u8]> {
+    Arc::clone(&self.bytes)
+  }
+
+  /// Bytes of 1-based `line`, without its line ending; `None` past the
+  /// last line.
+  #[must_use]
+  pub fn line_text(&self, line: u32) -> Option<&[u8]> {
+    let index = usize::try_from(line.checked_sub(1)?).ok()?;
+    let start = *self.line_starts.get(index)? as usize;
+    let end = self
+      .line_starts
+      .get(index + 1)
+      .map_or(self.bytes.len(), |&next| next as usize - 1);
+    let text = &self.bytes[start..end];
+    Some(text.strip_suffix(b"\r").unwrap_or(text))
+  }
+
   #[must_use]
   pub fn line_col(&self, byte_offset: u32) -> LineCol {
     if self.bytes.is_empty() {
@@ -88,8 +135,18 @@ impl SourceFile {
   }
 }
 
+/// A macro expansion that produced a span: the macro's name and the span
+/// of the invocation it replaced.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Expansion {
+  pub macro_name: String,
+  pub site: Span,
+}
+
 pub struct SourceMap {
   files: Vec<SourceFile>,
+  /// Expansion that produced each recorded span; see `record_expansion`.
+  expansions: HashMap<Span, Expansion>,
 }
 
 impl Default for SourceMap {
@@ -101,15 +158,25 @@ impl Default for SourceMap {
 impl SourceMap {
   #[must_use]
   pub fn new() -> Self {
-    Self { files: Vec::new() }
+    Self {
+      files: Vec::new(),
+      expansions: HashMap::new(),
+    }
   }
 
   pub fn add_file(&mut self, path: PathBuf, bytes: Vec<u8>) -> FileId {
-    self.add_internal(Some(path), bytes)
+    self.add_internal(Origin::Path(path), bytes)
   }
 
   pub fn add_stdin(&mut self, bytes: Vec<u8>) -> FileId {
-    self.add_internal(None, bytes)
+    self.add_internal(Origin::Stdin, bytes)
+  }
+
+  /// Adds an in-memory buffer with no path, such as the preprocessor's
+  /// `<command-line>` or `<built-in>` definitions. `name` is what
+  /// diagnostics print in place of a path.
+  pub fn add_virtual(&mut self, name: String, bytes: Vec<u8>) -> FileId {
+    self.add_internal(Origin::Virtual(name), bytes)
   }
 
   pub fn add_file_from_path(&mut self, path: &Path) -> Result<FileId, SourceError> {
@@ -120,45 +187,70 @@ impl SourceMap {
     Ok(self.add_file(path.to_path_buf(), bytes))
   }
 
+  /// Reads and adds each path in order, returning their ids. Stops at the
+  /// first unreadable path; files added before it stay in the map.
+  pub fn add_files(&mut self, paths: &[PathBuf]) -> Result<Vec<FileId>, SourceError> {
+    paths.iter().map(|path| self.add_file_from_path(path)).collect()
+  }
+
   #[must_use]
   pub fn file(&self, id: FileId) -> &SourceFile {
-    let idx = match usize::try_from(id.0) {
+    let idx = match usize::try_from(id.as_u32()) {
       Ok(v) => v,
-      Err(_) => panic!("invalid file id {}", id.0),
+      Err(_) => panic!("invalid file id {}", id.as_u32()),
     };
     &self.files[idx]
   }
 
+  /// Records that `span` came from expanding `macro_name` at `site`. The
+  /// preprocessor gives each expansion's tokens their own spans, so `site`
+  /// may itself be recorded, forming the chain diagnostics walk back to
+  /// the original invocation.
+  pub fn record_expansion(&mut self, span: Span, macro_name: impl Into<String>, site: Span) {
+    let expansion = Expansion {
+      macro_name: macro_name.into(),
+      site,
+    };
+    self.expansions.insert(span, expansion);
+  }
+
+  /// The expansion that produced `span`, if any was recorded.
+  #[must_use]
+  pub fn expansion(&self, span: Span) -> Option<&Expansion> {
+    self.expansions.get(&span)
+  }
+
+  /// Resolves `span`'s start to a line and column. A span running past the
+  /// end of its file is clamped there, shortening the resolved length.
   #[must_use]
   pub fn resolve_span(&self, span: Span) -> ResolvedSpan<'_> {
     let file = self.file(span.file());
-    let loc = file.line_col(span.start());
-    let source_name = match file.path() {
-      Some(path) => SourceName::Path(path),
-      None => SourceName::Stdin,
-    };
+    let file_len = u32::try_from(file.bytes.len()).unwrap_or(u32::MAX);
+    let start = span.start().min(file_len);
+    let end = span.end().min(file_len);
+    let loc = file.line_col(start);
 
     ResolvedSpan {
-      source_name,
+      source_name: file.name(),
       line: loc.line,
       column: loc.column,
-      length: span.len(),
+      length: end - start,
     }
   }
 
-  fn add_internal(&mut self, path: Option<PathBuf>, bytes: Vec<u8>) -> FileId {
+  fn add_internal(&mut self, origin: Origin, bytes: Vec<u8>) -> FileId {
     let next = match u32::try_from(self.files.len()) {
-      Ok(raw) => raw,
-      Err(_) => panic!("too many source files"),
+      Ok(raw) if raw != FileId::DUMMY.as_u32() => raw,
+      _ => panic!("too many source files"),
     };
 
-    let id = FileId(next);
+    let id = FileId::new(next);
     let line_starts = compute_line_starts(&bytes);
 
     self.files.push(SourceFile {
       id,
-      path,
-      bytes: bytes.into_boxed_slice(),
+      origin,
+      bytes: bytes.into(),
       line_starts: line_starts.into_boxed_slice(),
     });
 
@@ -175,11 +267,18 @@ pub enum SourceError {
   },
 }
 
+/// Offsets where each line starts. `\n`, `\r\n` and a bare `\r` (classic Mac
+/// OS) each end a line.
 fn compute_line_starts(bytes: &[u8]) -> Vec<u32> {
   let mut starts = vec![0];
 
   for (idx, byte) in bytes.iter().enumerate() {
-    if *byte != b'\n' {
+    let ends_line = match byte {
+      b'\n' => true,
+      b'\r' => bytes.get(idx + 1) != Some(&b'\n'),
+      _ => false,
+    };
+    if !ends_line {
       continue;
     }
 
@@ -195,12 +294,62 @@ fn compute_line_starts(bytes: &[u8]) -> Vec<u32> {
 
 #[cfg(test)]
 mod tests {
+  use std::{
+    path::Path,
+    sync::Arc,
+  };
+
   use super::{
+    FileId,
+    LineCol,
+    SourceError,
     SourceMap,
     SourceName,
   };
   use crate::span::Span;
 
+  #[test]
+  fn file_id_round_trips_through_raw_value() {
+    let mut map = SourceMap::new();
+    map.add_stdin(b"first".to_vec());
+    let issued = map.add_stdin(b"abc\nxy".to_vec());
+
+    let file = FileId::new(issued.as_u32());
+    assert_eq!(file, issued);
+    let span = Span::new(file, 4, 6);
+    let resolved = map.resolve_span(span);
+    assert_eq!((resolved.line, resolved.column, resolved.length), (2, 1, 2));
+  }
+
+  #[test]
+  fn line_text_strips_line_endings() {
+    let mut map = SourceMap::new();
+    let id = map.add_stdin(b"ab\r\n\ncd".to_vec());
+    let file = map.file(id);
+    assert_eq!(file.line_text(1), Some(&b"ab"[..]));
+    assert_eq!(file.line_text(2), Some(&b""[..]));
+    assert_eq!(file.line_text(3), Some(&b"cd"[..]));
+    assert_eq!(file.line_text(4), None);
+    assert_eq!(file.line_text(0), None);
+  }
+
+  #[test]
+  fn bare_carriage_returns_end_lines() {
+    let mut map = SourceMap::new();
+    let id = map.add_stdin(b"a\rb\rc".to_vec());
+    let file = map.file(id);
+    assert_eq!(file.line_col(4), LineCol { line: 3, column: 1 });
+    assert_eq!(file.line_text(2), Some(&b"b"[..]));
+    assert_eq!(file.line_text(3), Some(&b"c"[..]));
+    assert_eq!(file.line_text(4), None);
+
+    let id = map.add_stdin(b"a\r\nb".to_vec());
+    let file = map.file(id);
+    assert_eq!(file.line_col(3), LineCol { line: 2, column: 1 });
+    assert_eq!(file.line_text(1), Some(&b"a"[..]));
+    assert_eq!(file.line_text(3), None);
+  }
+
   #[test]
   fn line_col_resolves_start_of_file() {
     let mut map = SourceMap::new();
@@ -248,4 +397,75 @@ mod tests {
     assert_eq!(resolved.column, 1);
     assert_eq!(resolved.length, 3);
   }
+
+  #[test]
+  fn resolve_span_names_virtual_files() {
+    let mut map = SourceMap::new();
+    let file = map.add_virtual(String::from("<command-line>"), b"#define A 1\n#undef B\n".to_vec());
+    assert_eq!(map.file(file).path(), None);
+    let resolved = map.resolve_span(Span::new(file, 12, 18));
+    assert_eq!(resolved.source_name, SourceName::Virtual("<command-line>"));
+    assert_eq!((resolved.line, resolved.column, resolved.length), (2, 1, 6));
+    assert_eq!(resolved.source_name.to_string(), "<command-line>");
+    assert_eq!(SourceName::Stdin.to_string(), "<stdin>");
+  }
+
+  #[test]
+  fn source_names_display_like_gcc() {
+    let cases = [
+      (SourceName::Path(Path::new("src/foo.c")), "src/foo.c"),
+      (SourceName::Path(Path::new("./foo.c")), "./foo.c"),
+      (SourceName::Stdin, "<stdin>"),
+      (SourceName::Virtual("<built-in>"), "<built-in>"),
+      (SourceName::Virtual("<command-line>"), "<command-line>"),
+    ];
+    for (name, shown) in cases {
+      assert_eq!(name.to_string(), shown);
+    }
+  }
+
+  #[test]
+  fn resolve_span_clamps_end_past_eof() {
+    let mut map = SourceMap::new();
+    let file = map.add_stdin(b"abc\ndef".to_vec());
+    let resolved = map.resolve_span(Span::new(file, 5, 40));
+    assert_eq!((resolved.line, resolved.column), (2, 2));
+    assert_eq!(resolved.length, 2);
+
+    let resolved = map.resolve_span(Span::new(file, 20, 40));
+    assert_eq!((resolved.line, resolved.column), (2, 4));
+    assert_eq!(resolved.length, 0);
+  }
+
+  #[test]
+  fn add_files_reads_each_path_in_order() {
+    let dir = std::env::temp_dir().join(format!("slopcc-add-files-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let paths = [dir.join("a.c"), dir.join("b.c")];
+    std::fs::write(&paths[0], b"a").unwrap();
+    std::fs::write(&paths[1], b"b").unwrap();
+
+    let mut map = SourceMap::new();
+    let ids = map.add_files(&paths).unwrap();
+    assert_eq!(ids.len(), 2);
+    assert_ne!(ids[0], ids[1]);
+    assert_eq!(map.file(ids[1]).bytes(), b"b");
+
+    let missing = [paths[0].clone(), dir.join("missing.c")];
+    let err = map.add_files(&missing).unwrap_err();
+    assert!(matches!(err, SourceError::ReadFile { ref path, .. } if *path == missing[1]));
+    std::fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn shared_bytes_outlive_the_map() {
+    let mut map = SourceMap::new();
+    let id = map.add_virtual("<shared>".to_owned(), b"int x;\n".to_vec());
+    let bytes = map.file(id).shared_bytes();
+    let copy = Arc::clone(&bytes);
+    drop(map);
+    let reader = std::thread::spawn(move || copy.len());
+    assert_eq!(reader.join().unwrap(), 7);
+    assert_eq!(&bytes[..], b"int x;\n");
+  }
 }
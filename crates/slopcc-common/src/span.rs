@@ -1,4 +1,36 @@
-use crate::source::FileId;
+use core::ops::Range;
+
+#[cfg(feature = "alloc")]
+use alloc::{
+  borrow::Cow,
+  string::String,
+};
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct FileId(u32);
+
+impl FileId {
+  /// Reserved for [`Span::DUMMY`]; never issued by a `SourceMap`.
+  pub const DUMMY: Self = Self(u32::MAX);
+
+  /// Rebuilds an id from its [`FileId::as_u32`] value, for tooling that
+  /// reads spans back from serialized data. The id only means something to
+  /// the `SourceMap` that issued it; `file` panics on unknown ids.
+  #[must_use]
+  pub fn new(raw: u32) -> Self {
+    Self(raw)
+  }
+
+  #[must_use]
+  pub fn as_u32(self) -> u32 {
+    self.0
+  }
+
+  #[must_use]
+  pub fn new_for_tests(raw: u32) -> Self {
+    Self(raw)
+  }
+}
 
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
 pub struct Span {
@@ -8,6 +40,15 @@ pub struct Span {
 }
 
 impl Span {
+  /// Placeholder for synthesized tokens with no source location. Its file
+  /// is [`FileId::DUMMY`], which no `SourceMap` issues, so it must not be
+  /// resolved; renderers leave out the location and snippet instead.
+  pub const DUMMY: Self = Self {
+    file: FileId::DUMMY,
+    start: 0,
+    end: 0,
+  };
+
   #[must_use]
   pub fn new(file: FileId, start: u32, end: u32) -> Self {
     assert!(start <= end, "span start must be <= end");
@@ -23,6 +64,48 @@ impl Span {
     }
   }
 
+  /// The span over `range` in `file`; panics if the range is reversed.
+  #[must_use]
+  pub fn from_range(file: FileId, range: Range<u32>) -> Self {
+    Self::new(file, range.start, range.end)
+  }
+
+  /// The covered byte offsets, without the file.
+  #[must_use]
+  pub fn to_range(self) -> Range<u32> {
+    self.start..self.end
+  }
+
+  /// This span moved to start at `start`; panics if that is past the end.
+  #[must_use]
+  pub fn with_start(self, start: u32) -> Self {
+    Self::new(self.file, start, self.end)
+  }
+
+  /// This span moved to end at `end`; panics if that is before the start.
+  #[must_use]
+  pub fn with_end(self, end: u32) -> Self {
+    Self::new(self.file, self.start, end)
+  }
+
+  /// Extends the end by `n` bytes.
+  #[must_use]
+  pub fn grow(self, n: u32) -> Self {
+    let end = self.end.checked_add(n).expect("span end overflows u32");
+    Self::new(self.file, self.start, end)
+  }
+
+  /// Pulls the end in by `n` bytes, stopping at the start.
+  #[must_use]
+  pub fn shrink(self, n: u32) -> Self {
+    Self::new(self.file, self.start, self.end.saturating_sub(n).max(self.start))
+  }
+
+  #[must_use]
+  pub fn is_dummy(self) -> bool {
+    self.file == FileId::DUMMY
+  }
+
   #[must_use]
   pub fn file(self) -> FileId {
     self.file
@@ -47,12 +130,42 @@ impl Span {
   pub fn is_empty(self) -> bool {
     self.start == self.end
   }
+
+  /// Bytes covered by this span within `src`, the contents of its file.
+  #[must_use]
+  pub fn as_bytes(self, src: &[u8]) -> &[u8] {
+    &src[self.start as usize..self.end as usize]
+  }
+
+  /// [`Span::as_bytes`] as text for diagnostics; invalid UTF-8 becomes
+  /// U+FFFD. Borrows when the bytes are already valid.
+  #[cfg(feature = "alloc")]
+  #[must_use]
+  pub fn text_lossy(self, src: &[u8]) -> Cow<'_, str> {
+    String::from_utf8_lossy(self.as_bytes(src))
+  }
+}
+
+impl From<(FileId, Range<u32>)> for Span {
+  fn from((file, range): (FileId, Range<u32>)) -> Self {
+    Self::from_range(file, range)
+  }
+}
+
+impl From<Span> for Range<u32> {
+  fn from(span: Span) -> Self {
+    span.to_range()
+  }
 }
 
 #[cfg(test)]
 mod tests {
-  use super::Span;
-  use crate::source::FileId;
+  use core::ops::Range;
+
+  use super::{
+    FileId,
+    Span,
+  };
 
   #[test]
   fn span_len_is_half_open() {
@@ -60,6 +173,85 @@ mod tests {
     assert_eq!(span.len(), 3);
   }
 
+  #[cfg(feature = "alloc")]
+  #[test]
+  fn text_lossy_borrows_ascii_and_replaces_invalid_bytes() {
+    use alloc::borrow::Cow;
+
+    let file = FileId::new_for_tests(0);
+    let src = b"int foo; \xFFx\xC3";
+    let ascii = Span::new(file, 4, 7).text_lossy(src);
+    assert!(matches!(ascii, Cow::Borrowed("foo")));
+    assert_eq!(Span::new(file, 9, 12).text_lossy(src), "\u{FFFD}x\u{FFFD}");
+  }
+
+  #[test]
+  fn as_bytes_slices_source_bytes() {
+    let span = Span::new(FileId::new_for_tests(0), 4, 7);
+    assert_eq!(span.as_bytes(b"int foo;"), b"foo");
+  }
+
+  #[test]
+  fn with_start_and_with_end_keep_the_file() {
+    let file = FileId::new_for_tests(3);
+    let span = Span::new(file, 4, 8);
+    assert_eq!(span.with_start(6), Span::new(file, 6, 8));
+    assert_eq!(span.with_end(12), Span::new(file, 4, 12));
+    assert_eq!(span.with_start(8), Span::at(file, 8));
+  }
+
+  #[test]
+  #[should_panic(expected = "span start must be <= end")]
+  fn with_start_past_the_end_panics() {
+    let _ = Span::new(FileId::new_for_tests(0), 4, 8).with_start(9);
+  }
+
+  #[test]
+  #[should_panic(expected = "span start must be <= end")]
+  fn with_end_before_the_start_panics() {
+    let _ = Span::new(FileId::new_for_tests(0), 4, 8).with_end(3);
+  }
+
+  #[test]
+  fn grow_extends_the_end() {
+    let file = FileId::new_for_tests(1);
+    assert_eq!(Span::new(file, 2, 5).grow(3), Span::new(file, 2, 8));
+    assert_eq!(Span::at(file, 7).grow(0), Span::at(file, 7));
+  }
+
+  #[test]
+  fn shrink_clamps_at_the_start() {
+    let file = FileId::new_for_tests(1);
+    assert_eq!(Span::new(file, 2, 5).shrink(1), Span::new(file, 2, 4));
+    assert_eq!(Span::new(file, 2, 5).shrink(3), Span::at(file, 2));
+    assert_eq!(Span::new(file, 2, 5).shrink(10), Span::at(file, 2));
+  }
+
+  #[test]
+  fn ranges_round_trip_through_spans() {
+    let file = FileId::new_for_tests(2);
+    let span = Span::from_range(file, 3..9);
+    assert_eq!(span, Span::new(file, 3, 9));
+    assert_eq!(span.to_range(), 3..9);
+    assert_eq!(Span::from((file, 4..4)), Span::at(file, 4));
+    let range: Range<u32> = Span::new(file, 1, 6).into();
+    assert_eq!(range, 1..6);
+  }
+
+  #[test]
+  #[should_panic(expected = "span start must be <= end")]
+  fn reversed_range_panics() {
+    #[allow(clippy::reversed_empty_ranges)]
+    let _ = Span::from_range(FileId::new_for_tests(0), 5..2);
+  }
+
+  #[test]
+  fn only_the_dummy_span_is_dummy() {
+    assert!(Span::DUMMY.is_dummy());
+    assert!(Span::DUMMY.is_empty());
+    assert!(!Span::new(FileId::new_for_tests(0), 0, 0).is_dummy());
+  }
+
   #[test]
   fn zero_length_span_is_empty() {
     let span = Span::at(FileId::new_for_tests(1), 12);
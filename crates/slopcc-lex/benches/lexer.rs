@@ -0,0 +1,51 @@
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use slopcc_arena::intern::Interner;
+use slopcc_common::source::FileId;
+use slopcc_lex::Lexer;
+
+/// One function's worth of typical C: declarations, literals of every kind,
+/// comments, a directive and most punctuators.
+const CHUNK: &str = r#"#include <stdio.h>
+/* Sums the squares of the first n values. */
+static unsigned long long sum_squares(const int *values, size_t n) {
+    unsigned long long total = 0ULL;
+    for (size_t i = 0; i < n; ++i) {
+        total += (unsigned long long)values[i] * values[i]; // widen first
+    }
+    return total >> 1 ? total : 0x1p-3 + .5e+10f;
+}
+
+int main(int argc, char **argv) {
+    const char *greeting = u8"hello, world\n";
+    wchar_t wide = L'\x41';
+    if (argc > 1 && argv[1][0] != '-') printf("%s %d\n", greeting, argc);
+    return sum_squares((int[]){1, 2, 3}, 3) % 7 == 0 ? 0 : 1;
+}
+"#;
+
+/// About 1 MiB of source made of repeated [`CHUNK`]s.
+fn corpus() -> Vec<u8> {
+    CHUNK.repeat((1 << 20) / CHUNK.len()).into_bytes()
+}
+
+fn tokenize(c: &mut Criterion) {
+    let src = corpus();
+    let file = FileId::new(0);
+    let mut group = c.benchmark_group("lexer");
+    group.throughput(Throughput::Bytes(src.len() as u64));
+    group.bench_function("tokenize", |b| {
+        b.iter(|| Lexer::tokenize(black_box(&src), file).len());
+    });
+    group.bench_function("tokenize_interned", |b| {
+        b.iter(|| {
+            let mut interner = Interner::new();
+            Lexer::new(black_box(&src), file).with_interner(&mut interner).count()
+        });
+    });
+    group.finish();
+}
+
+criterion_group!(benches, tokenize);
+criterion_main!(benches);
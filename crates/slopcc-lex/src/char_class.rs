@@ -0,0 +1,55 @@
+/// What `Lexer::scan` does with the first byte of a token. Looking the class
+/// up in [`CHAR_CLASS`] replaces a chain of byte comparisons in the hot loop.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum CharClass {
+    /// Space, tab, `\r`, vertical tab or form feed.
+    Whitespace,
+    Newline,
+    Nul,
+    /// `/`: a comment or a punctuator.
+    Slash,
+    Digit,
+    /// `.`: a pp-number before a digit, otherwise a punctuator.
+    Dot,
+    /// `L`, `u`, `U`: an identifier or a literal's encoding prefix.
+    Prefix,
+    /// Any other letter or `_`.
+    IdentStart,
+    /// `$`: an identifier byte under `LexOptions::dollar_idents`.
+    Dollar,
+    DoubleQuote,
+    SingleQuote,
+    /// `0x80..=0xFF`: bytes of non-ASCII text, lexed a run at a time.
+    NonAscii,
+    /// Punctuators and bytes no token starts with.
+    Other,
+}
+
+/// [`CharClass`] of every byte, built at compile time.
+pub(crate) static CHAR_CLASS: [CharClass; 256] = {
+    let mut table = [CharClass::Other; 256];
+    let mut byte = 0;
+    while byte < table.len() {
+        table[byte] = classify(byte as u8);
+        byte += 1;
+    }
+    table
+};
+
+const fn classify(byte: u8) -> CharClass {
+    match byte {
+        b' ' | b'\t' | b'\r' | 0x0B | 0x0C => CharClass::Whitespace,
+        b'\n' => CharClass::Newline,
+        0 => CharClass::Nul,
+        b'/' => CharClass::Slash,
+        b'0'..=b'9' => CharClass::Digit,
+        b'.' => CharClass::Dot,
+        b'L' | b'u' | b'U' => CharClass::Prefix,
+        b'a'..=b'z' | b'A'..=b'Z' | b'_' => CharClass::IdentStart,
+        b'$' => CharClass::Dollar,
+        b'"' => CharClass::DoubleQuote,
+        b'\'' => CharClass::SingleQuote,
+        0x80..=0xFF => CharClass::NonAscii,
+        _ => CharClass::Other,
+    }
+}
@@ -24,6 +24,15 @@ impl<'src> Cursor<'src> {
         self.bytes.get(self.pos + 1).copied()
     }
 
+    pub(crate) fn peek_at(&self, n: usize) -> Option<u8> {
+        self.bytes.get(self.pos + n).copied()
+    }
+
+    /// Unconsumed bytes; empty at EOF.
+    pub(crate) fn rest(&self) -> &'src [u8] {
+        self.bytes.get(self.pos..).unwrap_or_default()
+    }
+
     pub(crate) fn advance(&mut self) -> Option<u8> {
         let byte = self.peek()?;
         self.pos += 1;
@@ -39,6 +48,15 @@ impl<'src> Cursor<'src> {
         }
     }
 
+    /// Consumes `s` only if the upcoming bytes match it exactly.
+    pub(crate) fn eat_str(&mut self, s: &[u8]) -> bool {
+        let matches = self.rest().starts_with(s);
+        if matches {
+            self.pos += s.len();
+        }
+        matches
+    }
+
     pub(crate) fn eat_while(&mut self, mut pred: impl FnMut(u8) -> bool) {
         while let Some(byte) = self.peek() {
             if !pred(byte) {
@@ -47,6 +65,25 @@ impl<'src> Cursor<'src> {
             self.pos += 1;
         }
     }
+
+    /// Current position, for a later [`Cursor::reset`].
+    pub(crate) const fn mark(&self) -> usize {
+        self.pos
+    }
+
+    /// Moves back (or forward) to a position taken with [`Cursor::mark`].
+    pub(crate) fn reset(&mut self, mark: usize) {
+        debug_assert!(mark <= self.bytes.len(), "mark past end of input");
+        self.pos = mark;
+    }
+
+    /// Advances to the next `\n`, leaving it unconsumed, or to EOF.
+    pub(crate) fn eat_to_newline(&mut self) {
+        self.pos = match self.rest().iter().position(|&byte| byte == b'\n') {
+            Some(offset) => self.pos + offset,
+            None => self.bytes.len(),
+        };
+    }
 }
 
 #[cfg(test)]
@@ -71,6 +108,83 @@ mod tests {
         assert_eq!(cursor.peek(), Some(b'a'));
     }
 
+    #[test]
+    fn eat_to_newline_stops_at_the_newline() {
+        let mut cursor = Cursor::new(b"ab\r\ncd");
+        cursor.eat_to_newline();
+        assert_eq!(cursor.pos(), 3);
+        assert_eq!(cursor.peek(), Some(b'\n'));
+        cursor.eat_to_newline();
+        assert_eq!(cursor.pos(), 3);
+
+        let _ = cursor.advance();
+        cursor.eat_to_newline();
+        assert!(cursor.is_eof());
+        cursor.eat_to_newline();
+        assert_eq!(cursor.pos(), 6);
+    }
+
+    #[test]
+    fn reset_returns_to_a_mark() {
+        let mut cursor = Cursor::new(b"abc");
+        let start = cursor.mark();
+        let _ = cursor.advance();
+        let middle = cursor.mark();
+        let _ = cursor.advance();
+        cursor.reset(middle);
+        assert_eq!(cursor.pos(), 1);
+        assert_eq!(cursor.advance(), Some(b'b'));
+
+        cursor.eat_to_newline();
+        assert!(cursor.is_eof());
+        cursor.reset(start);
+        assert_eq!(cursor.rest(), b"abc");
+        assert_eq!(cursor.advance(), Some(b'a'));
+    }
+
+    #[test]
+    fn peek_at_looks_ahead_without_advancing() {
+        let mut cursor = Cursor::new(b"abc");
+        assert_eq!(cursor.peek_at(0), Some(b'a'));
+        assert_eq!(cursor.peek_at(1), Some(b'b'));
+        assert_eq!(cursor.peek_at(2), Some(b'c'));
+        assert_eq!(cursor.peek_at(3), None);
+        assert_eq!(cursor.pos(), 0);
+
+        let _ = cursor.advance();
+        assert_eq!(cursor.peek_at(0), Some(b'b'));
+        assert_eq!(cursor.peek_at(2), None);
+    }
+
+    #[test]
+    fn eat_str_matches_whole_literal() {
+        let mut cursor = Cursor::new(b"%:%:x");
+        assert!(cursor.eat_str(b"%:%:"));
+        assert_eq!(cursor.pos(), 4);
+        assert_eq!(cursor.peek(), Some(b'x'));
+    }
+
+    #[test]
+    fn eat_str_partial_mismatch_consumes_nothing() {
+        let mut cursor = Cursor::new(b"%:%>");
+        assert!(!cursor.eat_str(b"%:%:"));
+        assert_eq!(cursor.pos(), 0);
+        assert!(!cursor.eat_str(b"%:%>!"));
+        assert_eq!(cursor.pos(), 0);
+    }
+
+    #[test]
+    fn rest_shrinks_as_cursor_advances() {
+        let mut cursor = Cursor::new(b"ab");
+        assert_eq!(cursor.rest(), b"ab");
+        let _ = cursor.advance();
+        assert_eq!(cursor.rest(), b"b");
+        let _ = cursor.advance();
+        assert!(cursor.rest().is_empty());
+        let _ = cursor.advance();
+        assert!(cursor.rest().is_empty());
+    }
+
     #[test]
     fn eof_behavior() {
         let mut cursor = Cursor::new(b"");
@@ -79,5 +193,6 @@ mod tests {
         assert_eq!(cursor.peek_next(), None);
         assert_eq!(cursor.advance(), None);
         assert!(!cursor.eat(b'x'));
+        assert!(cursor.rest().is_empty());
     }
 }
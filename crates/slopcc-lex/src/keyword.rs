@@ -0,0 +1,225 @@
+/// C11 keywords (§6.4.1).
+///
+/// The lexer always emits identifier-like tokens as `TokenKind::Ident`;
+/// callers classify the spelling with [`keyword`] when they need to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Keyword {
+    Auto,
+    Break,
+    Case,
+    Char,
+    Const,
+    Continue,
+    Default,
+    Do,
+    Double,
+    Else,
+    Enum,
+    Extern,
+    Float,
+    For,
+    Goto,
+    If,
+    Inline,
+    Int,
+    Long,
+    Register,
+    Restrict,
+    Return,
+    Short,
+    Signed,
+    Sizeof,
+    Static,
+    Struct,
+    Switch,
+    Typedef,
+    Union,
+    Unsigned,
+    Void,
+    Volatile,
+    While,
+    Alignas,
+    Alignof,
+    Atomic,
+    Bool,
+    Complex,
+    Generic,
+    Imaginary,
+    Noreturn,
+    StaticAssert,
+    ThreadLocal,
+}
+
+impl Keyword {
+    /// Source spelling of the keyword.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Auto => "auto",
+            Self::Break => "break",
+            Self::Case => "case",
+            Self::Char => "char",
+            Self::Const => "const",
+            Self::Continue => "continue",
+            Self::Default => "default",
+            Self::Do => "do",
+            Self::Double => "double",
+            Self::Else => "else",
+            Self::Enum => "enum",
+            Self::Extern => "extern",
+            Self::Float => "float",
+            Self::For => "for",
+            Self::Goto => "goto",
+            Self::If => "if",
+            Self::Inline => "inline",
+            Self::Int => "int",
+            Self::Long => "long",
+            Self::Register => "register",
+            Self::Restrict => "restrict",
+            Self::Return => "return",
+            Self::Short => "short",
+            Self::Signed => "signed",
+            Self::Sizeof => "sizeof",
+            Self::Static => "static",
+            Self::Struct => "struct",
+            Self::Switch => "switch",
+            Self::Typedef => "typedef",
+            Self::Union => "union",
+            Self::Unsigned => "unsigned",
+            Self::Void => "void",
+            Self::Volatile => "volatile",
+            Self::While => "while",
+            Self::Alignas => "_Alignas",
+            Self::Alignof => "_Alignof",
+            Self::Atomic => "_Atomic",
+            Self::Bool => "_Bool",
+            Self::Complex => "_Complex",
+            Self::Generic => "_Generic",
+            Self::Imaginary => "_Imaginary",
+            Self::Noreturn => "_Noreturn",
+            Self::StaticAssert => "_Static_assert",
+            Self::ThreadLocal => "_Thread_local",
+        }
+    }
+}
+
+/// Classifies an identifier spelling as a C11 keyword, if it is one.
+#[must_use]
+pub fn keyword(text: &[u8]) -> Option<Keyword> {
+    let keyword = match text {
+        b"auto" => Keyword::Auto,
+        b"break" => Keyword::Break,
+        b"case" => Keyword::Case,
+        b"char" => Keyword::Char,
+        b"const" => Keyword::Const,
+        b"continue" => Keyword::Continue,
+        b"default" => Keyword::Default,
+        b"do" => Keyword::Do,
+        b"double" => Keyword::Double,
+        b"else" => Keyword::Else,
+        b"enum" => Keyword::Enum,
+        b"extern" => Keyword::Extern,
+        b"float" => Keyword::Float,
+        b"for" => Keyword::For,
+        b"goto" => Keyword::Goto,
+        b"if" => Keyword::If,
+        b"inline" => Keyword::Inline,
+        b"int" => Keyword::Int,
+        b"long" => Keyword::Long,
+        b"register" => Keyword::Register,
+        b"restrict" => Keyword::Restrict,
+        b"return" => Keyword::Return,
+        b"short" => Keyword::Short,
+        b"signed" => Keyword::Signed,
+        b"sizeof" => Keyword::Sizeof,
+        b"static" => Keyword::Static,
+        b"struct" => Keyword::Struct,
+        b"switch" => Keyword::Switch,
+        b"typedef" => Keyword::Typedef,
+        b"union" => Keyword::Union,
+        b"unsigned" => Keyword::Unsigned,
+        b"void" => Keyword::Void,
+        b"volatile" => Keyword::Volatile,
+        b"while" => Keyword::While,
+        b"_Alignas" => Keyword::Alignas,
+        b"_Alignof" => Keyword::Alignof,
+        b"_Atomic" => Keyword::Atomic,
+        b"_Bool" => Keyword::Bool,
+        b"_Complex" => Keyword::Complex,
+        b"_Generic" => Keyword::Generic,
+        b"_Imaginary" => Keyword::Imaginary,
+        b"_Noreturn" => Keyword::Noreturn,
+        b"_Static_assert" => Keyword::StaticAssert,
+        b"_Thread_local" => Keyword::ThreadLocal,
+        _ => return None,
+    };
+    Some(keyword)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{keyword, Keyword};
+
+    const C11_KEYWORDS: [Keyword; 44] = [
+        Keyword::Auto,
+        Keyword::Break,
+        Keyword::Case,
+        Keyword::Char,
+        Keyword::Const,
+        Keyword::Continue,
+        Keyword::Default,
+        Keyword::Do,
+        Keyword::Double,
+        Keyword::Else,
+        Keyword::Enum,
+        Keyword::Extern,
+        Keyword::Float,
+        Keyword::For,
+        Keyword::Goto,
+        Keyword::If,
+        Keyword::Inline,
+        Keyword::Int,
+        Keyword::Long,
+        Keyword::Register,
+        Keyword::Restrict,
+        Keyword::Return,
+        Keyword::Short,
+        Keyword::Signed,
+        Keyword::Sizeof,
+        Keyword::Static,
+        Keyword::Struct,
+        Keyword::Switch,
+        Keyword::Typedef,
+        Keyword::Union,
+        Keyword::Unsigned,
+        Keyword::Void,
+        Keyword::Volatile,
+        Keyword::While,
+        Keyword::Alignas,
+        Keyword::Alignof,
+        Keyword::Atomic,
+        Keyword::Bool,
+        Keyword::Complex,
+        Keyword::Generic,
+        Keyword::Imaginary,
+        Keyword::Noreturn,
+        Keyword::StaticAssert,
+        Keyword::ThreadLocal,
+    ];
+
+    #[test]
+    fn classifies_all_c11_keywords() {
+        for expected in C11_KEYWORDS {
+            assert_eq!(keyword(expected.as_str().as_bytes()), Some(expected));
+        }
+    }
+
+    #[test]
+    fn rejects_non_keywords() {
+        assert_eq!(keyword(b"integer"), None);
+        assert_eq!(keyword(b"in"), None);
+        assert_eq!(keyword(b"Int"), None);
+        assert_eq!(keyword(b"_Static"), None);
+        assert_eq!(keyword(b""), None);
+    }
+}
@@ -1,77 +1,208 @@
-use slopcc_common::{source::FileId, span::Span};
+use std::iter::FusedIterator;
+
+use slopcc_arena::intern::Interner;
+use slopcc_common::{
+    diag::{Diagnostic, Diagnostics},
+    source::FileId,
+    span::Span,
+};
 
 use crate::{
+    char_class::{CharClass, CHAR_CLASS},
     cursor::Cursor,
+    stats::LexStats,
     token::{Token, TokenKind},
 };
 
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Caller-selected lexer behavior. The default matches `Lexer::new`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LexOptions {
+    /// `next_significant` returns `Newline` tokens instead of skipping them.
+    pub significant_newlines: bool,
+    /// Comments lex as `Comment` tokens. When unset they lex as `Whitespace`
+    /// spanning the whole comment, matching translation phase 3.
+    pub preserve_comments: bool,
+    /// Source is C90, where `//` comments are an extension: each one draws
+    /// a `pedantic`-coded warning.
+    pub c90: bool,
+    /// `$` is an identifier byte, as in GNU C. When unset it is a stray
+    /// character.
+    pub dollar_idents: bool,
+    /// `u8'x'` is a UTF-8 character constant (C23). Otherwise `u8` lexes as
+    /// an identifier before the `'x'`, with a warning.
+    pub u8_char_consts: bool,
+    /// `'` followed by a digit or identifier letter continues a pp-number
+    /// as a digit separator (`1'000`), as C23 allows. The driver also sets
+    /// it for the GNU dialects. When unset the `'` ends the number and
+    /// starts a character constant.
+    pub digit_separators: bool,
+    /// A maximal run of whitespace and comments on one line lexes as a
+    /// single token spanning the run: `Comment` if it holds a comment and
+    /// comments are preserved, `Whitespace` otherwise.
+    pub collapse_trivia: bool,
+}
+
+impl Default for LexOptions {
+    fn default() -> Self {
+        Self {
+            significant_newlines: false,
+            preserve_comments: true,
+            c90: false,
+            dollar_idents: false,
+            u8_char_consts: false,
+            digit_separators: false,
+            collapse_trivia: false,
+        }
+    }
+}
+
 /// Preprocessing-token lexer for C source bytes.
 pub struct Lexer<'src> {
     cursor: Cursor<'src>,
     src: &'src [u8],
     file: FileId,
+    options: LexOptions,
+    interner: Option<&'src mut Interner>,
+    diagnostics: Diagnostics,
+    finished: bool,
+    after_trivia: bool,
+    at_bol: bool,
+    header_name: bool,
 }
 
 impl<'src> Lexer<'src> {
     #[must_use]
     pub fn new(src: &'src [u8], file: FileId) -> Self {
+        Self::with_options(src, file, LexOptions::default())
+    }
+
+    #[must_use]
+    pub fn with_options(src: &'src [u8], file: FileId, options: LexOptions) -> Self {
+        let mut cursor = Cursor::new(src);
+        let _ = cursor.eat_str(UTF8_BOM);
         Self {
-            cursor: Cursor::new(src),
+            cursor,
             src,
             file,
+            options,
+            interner: None,
+            diagnostics: Diagnostics::new(),
+            finished: false,
+            after_trivia: false,
+            at_bol: true,
+            header_name: false,
         }
     }
 
+    /// Interns every `Ident` and `PpNumber` spelling into `interner` and
+    /// records it in `Token::symbol`. Share one interner across files so
+    /// equal spellings get equal symbols everywhere.
     #[must_use]
-    pub fn next_token(&mut self) -> Token {
-        if self.cursor.is_eof() {
-            let pos = self.pos32();
-            return Token::new(TokenKind::Eof, Span::at(self.file, pos));
-        }
-
-        let byte = self.cursor.peek().unwrap_or_default();
-
-        if is_whitespace_no_newline(byte) {
-            return self.whitespace();
-        }
+    pub fn with_interner(mut self, interner: &'src mut Interner) -> Self {
+        self.interner = Some(interner);
+        self
+    }
 
-        if byte == b'\n' {
-            let start = self.cursor.pos();
-            let _ = self.cursor.advance();
-            return self.make_token(start, TokenKind::Newline);
+    #[must_use]
+    pub fn next_token(&mut self) -> Token {
+        let mut token = if self.header_name {
+            self.scan_header_name()
+        } else {
+            self.scan()
+        };
+        if self.options.collapse_trivia
+            && matches!(token.kind, TokenKind::Whitespace | TokenKind::Comment)
+        {
+            token = self.collapse_trivia(token);
         }
+        self.finish(token)
+    }
 
-        if byte == b'/' {
-            if self.cursor.peek_next() == Some(b'/') {
-                return self.line_comment();
-            }
-            if self.cursor.peek_next() == Some(b'*') {
-                return self.block_comment();
+    /// Extends the trivia `token` over any whitespace and comments that
+    /// follow it on the same line.
+    fn collapse_trivia(&mut self, mut token: Token) -> Token {
+        while self.at_trivia() {
+            let next = self.scan();
+            if next.kind == TokenKind::Comment {
+                token.kind = TokenKind::Comment;
             }
+            token.span = Span::new(self.file, token.span.start(), next.span.end());
         }
+        token
+    }
 
-        if byte.is_ascii_digit()
-            || (byte == b'.' && self.cursor.peek_next().is_some_and(|b| b.is_ascii_digit()))
-        {
-            return self.pp_number();
-        }
-
-        if matches!(byte, b'L' | b'u' | b'U') {
-            return self.ident_or_string_prefix();
+    fn at_trivia(&self) -> bool {
+        match self.cursor.peek() {
+            Some(b'/') => matches!(self.cursor.peek_next(), Some(b'/' | b'*')),
+            Some(byte) => is_whitespace_no_newline(byte),
+            None => false,
         }
+    }
 
-        if is_ident_start(byte) {
-            return self.ident();
+    /// Next token that is not whitespace or a comment. Newlines are skipped
+    /// too unless `LexOptions::significant_newlines` is set.
+    #[must_use]
+    pub fn next_significant(&mut self) -> Token {
+        loop {
+            let token = self.next_token();
+            match token.kind {
+                TokenKind::Whitespace | TokenKind::Comment => {}
+                TokenKind::Newline if !self.options.significant_newlines => {}
+                _ => return token,
+            }
         }
+    }
 
-        if byte == b'"' {
-            let _ = self.cursor.advance();
-            return self.string_literal(0);
+    fn scan(&mut self) -> Token {
+        if self.cursor.is_eof() {
+            let pos = self.pos32();
+            return Token::new(TokenKind::Eof, Span::at(self.file, pos));
         }
 
-        if byte == b'\'' {
-            let _ = self.cursor.advance();
-            return self.char_const(0);
+        let byte = self.cursor.peek().unwrap_or_default();
+        match CHAR_CLASS[usize::from(byte)] {
+            CharClass::Whitespace => return self.whitespace(),
+            CharClass::Newline => {
+                let start = self.cursor.pos();
+                let _ = self.cursor.advance();
+                return self.make_token(start, TokenKind::Newline);
+            }
+            CharClass::Nul => {
+                let start = self.cursor.pos();
+                let _ = self.cursor.advance();
+                let token = self.make_token(start, TokenKind::Unknown);
+                self.diagnostics
+                    .push(Diagnostic::error("null character in source", token.span));
+                return token;
+            }
+            CharClass::Slash if matches!(self.cursor.peek_next(), Some(b'/' | b'*')) => {
+                let start = self.cursor.pos();
+                if self.cursor.peek_next() == Some(b'/') {
+                    self.line_comment();
+                } else {
+                    self.block_comment();
+                }
+                return self.make_token(start, self.comment_kind());
+            }
+            CharClass::Digit => return self.pp_number(),
+            CharClass::Dot if self.cursor.peek_next().is_some_and(|b| b.is_ascii_digit()) => {
+                return self.pp_number();
+            }
+            CharClass::Prefix => return self.ident_or_string_prefix(),
+            CharClass::IdentStart => return self.ident(),
+            CharClass::Dollar if self.options.dollar_idents => return self.ident(),
+            CharClass::DoubleQuote => {
+                let _ = self.cursor.advance();
+                return self.string_literal(0);
+            }
+            CharClass::SingleQuote => {
+                let _ = self.cursor.advance();
+                return self.char_const(0);
+            }
+            CharClass::NonAscii => return self.non_ascii_run(),
+            CharClass::Slash | CharClass::Dot | CharClass::Dollar | CharClass::Other => {}
         }
 
         let start = self.cursor.pos();
@@ -93,44 +224,126 @@ impl<'src> Lexer<'src> {
         out
     }
 
+    /// [`Lexer::tokenize`] plus per-kind [`LexStats`] for the tokens.
+    #[must_use]
+    pub fn tokenize_with_stats(src: &'src [u8], file: FileId) -> (Vec<Token>, LexStats) {
+        let tokens = Self::tokenize(src, file);
+        let mut stats = LexStats::default();
+        for token in &tokens {
+            stats.record(token.kind);
+        }
+        (tokens, stats)
+    }
+
+    /// Discards the rest of the current line, so the next token is its
+    /// `Newline` (or `Eof`). The preprocessor uses this to recover from a
+    /// malformed directive.
+    pub fn skip_to_newline(&mut self) {
+        self.cursor.eat_to_newline();
+        self.after_trivia = true;
+    }
+
+    /// Significant tokens of a directive, for use right after its `bol`
+    /// `Hash`: everything up to, not including, the `Newline` or `Eof` that
+    /// ends the logical line. Whitespace and comments are dropped (tokens
+    /// keep `leading_ws`), and a backslash-newline continues the line
+    /// without joining the tokens on either side of it.
+    #[must_use]
+    pub fn lex_directive_line(&mut self) -> Vec<Token> {
+        let mut tokens = Vec::new();
+        loop {
+            if self.cursor.eat_str(b"\\\n") || self.cursor.eat_str(b"\\\r\n") {
+                continue;
+            }
+            if matches!(self.cursor.peek(), None | Some(b'\n')) {
+                return tokens;
+            }
+            let token = self.next_token();
+            if !token.kind.is_trivia() {
+                tokens.push(token);
+            }
+        }
+    }
+
+    /// Diagnostics reported so far.
+    #[must_use]
+    pub fn diagnostics(&self) -> &Diagnostics {
+        &self.diagnostics
+    }
+
+    /// Moves out the diagnostics reported so far.
+    #[must_use]
+    pub fn take_diagnostics(&mut self) -> Diagnostics {
+        std::mem::take(&mut self.diagnostics)
+    }
+
+    /// Source bytes spelled by `token`.
+    #[must_use]
+    pub fn text(&self, token: Token) -> &'src [u8] {
+        token.span.as_bytes(self.src)
+    }
+
+    /// Makes `next_token` lex a header name, as after `#include`. The mode
+    /// survives whitespace and comments and clears itself at the next other
+    /// token, `Newline` included, whether or not that is a `HeaderName`.
+    pub fn expect_header_name(&mut self) {
+        self.header_name = true;
+    }
+
     #[must_use]
     pub fn lex_header_name(&mut self) -> Token {
+        let token = self.scan_header_name();
+        self.finish(token)
+    }
+
+    /// Lexes a `<...>` or `"..."` header name. Input that does not start
+    /// with `<` or `"` is lexed as ordinary tokens (C11 §6.10.2p4), so
+    /// `MACRO` is an `Ident`. A name missing its closing `>` or `"` before
+    /// the end of the line is an error and lexes as one `Unknown` token up
+    /// to the line end.
+    fn scan_header_name(&mut self) -> Token {
         let start = self.cursor.pos();
-        match self.cursor.peek() {
-            Some(b'<') => {
+        let close = match self.cursor.peek() {
+            Some(b'<') => b'>',
+            Some(b'"') => b'"',
+            _ => return self.scan(),
+        };
+        let _ = self.cursor.advance();
+        while let Some(byte) = self.cursor.peek() {
+            if byte == close {
                 let _ = self.cursor.advance();
-                while let Some(byte) = self.cursor.peek() {
-                    if byte == b'>' {
-                        let _ = self.cursor.advance();
-                        return self.make_token(start, TokenKind::HeaderName);
-                    }
-                    if byte == b'\n' {
-                        break;
-                    }
-                    let _ = self.cursor.advance();
-                }
-                self.make_token(start, TokenKind::Unknown)
+                return self.make_token(start, TokenKind::HeaderName);
             }
-            Some(b'"') => {
-                let _ = self.cursor.advance();
-                while let Some(byte) = self.cursor.peek() {
-                    if byte == b'"' {
-                        let _ = self.cursor.advance();
-                        return self.make_token(start, TokenKind::HeaderName);
-                    }
-                    if byte == b'\n' {
-                        break;
-                    }
-                    let _ = self.cursor.advance();
-                }
-                self.make_token(start, TokenKind::Unknown)
+            if byte == b'\n' {
+                break;
             }
-            Some(_) => {
-                let _ = self.cursor.advance();
-                self.make_token(start, TokenKind::Unknown)
+            let _ = self.cursor.advance();
+        }
+        let token = self.make_token(start, TokenKind::Unknown);
+        self.diagnostics.push(Diagnostic::error(
+            format!("missing terminating {} character", char::from(close)),
+            token.span,
+        ));
+        token
+    }
+
+    fn finish(&mut self, mut token: Token) -> Token {
+        if let Some(interner) = self.interner.as_deref_mut() {
+            if matches!(token.kind, TokenKind::Ident | TokenKind::PpNumber) {
+                let text = std::str::from_utf8(token.span.as_bytes(self.src));
+                token.symbol = text.ok().map(|text| interner.intern(text));
             }
-            None => Token::new(TokenKind::Eof, Span::at(self.file, self.pos32())),
         }
+        token.leading_ws = self.after_trivia;
+        token.bol = self.at_bol;
+        self.after_trivia = token.kind.is_trivia();
+        self.header_name &= matches!(token.kind, TokenKind::Whitespace | TokenKind::Comment);
+        match token.kind {
+            TokenKind::Newline => self.at_bol = true,
+            TokenKind::Whitespace | TokenKind::Comment => {}
+            _ => self.at_bol = false,
+        }
+        token
     }
 
     fn whitespace(&mut self) -> Token {
@@ -139,77 +352,121 @@ impl<'src> Lexer<'src> {
         self.make_token(start, TokenKind::Whitespace)
     }
 
-    fn line_comment(&mut self) -> Token {
-        let start = self.cursor.pos();
+    fn comment_kind(&self) -> TokenKind {
+        if self.options.preserve_comments {
+            TokenKind::Comment
+        } else {
+            TokenKind::Whitespace
+        }
+    }
+
+    fn line_comment(&mut self) {
+        if self.options.c90 {
+            let start = self.pos32();
+            let span = Span::new(self.file, start, start + 2);
+            self.diagnostics.push(
+                Diagnostic::warning("C++ style comments are not allowed in ISO C90", span)
+                    .with_code("pedantic"),
+            );
+        }
         let _ = self.cursor.advance();
         let _ = self.cursor.advance();
-        self.cursor.eat_while(|byte| byte != b'\n');
-        self.make_token(start, TokenKind::Comment)
+        while let Some(byte) = self.cursor.peek() {
+            if byte == b'\n' || (byte == b'\r' && self.cursor.peek_next() == Some(b'\n')) {
+                break;
+            }
+            let _ = self.cursor.advance();
+        }
     }
 
-    fn block_comment(&mut self) -> Token {
-        let start = self.cursor.pos();
+    fn block_comment(&mut self) {
         let _ = self.cursor.advance();
         let _ = self.cursor.advance();
         while let Some(byte) = self.cursor.advance() {
             if byte == b'*' && self.cursor.eat(b'/') {
                 break;
             }
+            if byte == b'/' && self.cursor.peek() == Some(b'*') {
+                let start = self.pos32() - 1;
+                let span = Span::new(self.file, start, start + 2);
+                self.diagnostics.push(
+                    Diagnostic::warning("\"/*\" within comment", span).with_code("comment"),
+                );
+            }
         }
-        self.make_token(start, TokenKind::Comment)
     }
 
+    /// Scans a literal behind an `L`, `U`, `u` or `u8` encoding prefix.
+    /// Without a quote after the prefix, the cursor backs off to the start
+    /// and the word lexes as an identifier.
     fn ident_or_string_prefix(&mut self) -> Token {
-        let start = self.cursor.pos();
-        let first = self.cursor.advance().unwrap_or_default();
+        let start = self.cursor.mark();
+        let prefix_len = if self.cursor.eat_str(b"u8") {
+            2
+        } else {
+            let _ = self.cursor.advance();
+            1
+        };
 
-        match first {
-            b'L' | b'U' => {
-                if self.cursor.eat(b'"') {
-                    return self.string_literal(1);
-                }
-                if self.cursor.eat(b'\'') {
-                    return self.char_const(1);
-                }
+        match self.cursor.peek() {
+            Some(b'"') => {
+                let _ = self.cursor.advance();
+                return self.string_literal(prefix_len);
             }
-            b'u' => {
-                if self.cursor.eat(b'8') {
-                    if self.cursor.eat(b'"') {
-                        return self.string_literal(2);
-                    }
-                    self.cursor.eat_while(is_ident_continue);
-                    return self.make_token(start, TokenKind::Ident);
-                }
-
-                if self.cursor.eat(b'"') {
-                    return self.string_literal(1);
-                }
-                if self.cursor.eat(b'\'') {
-                    return self.char_const(1);
-                }
+            Some(b'\'') if prefix_len == 1 || self.options.u8_char_consts => {
+                let _ = self.cursor.advance();
+                return self.char_const(prefix_len);
+            }
+            Some(b'\'') => {
+                let span = Span::new(self.file, start as u32, self.pos32() + 1);
+                self.diagnostics.push(Diagnostic::warning(
+                    "'u8' character constants are a C23 feature; \
+                     'u8' is an identifier here",
+                    span,
+                ));
             }
             _ => {}
         }
 
-        self.cursor.eat_while(is_ident_continue);
-        self.make_token(start, TokenKind::Ident)
+        self.cursor.reset(start);
+        self.ident()
     }
 
     fn ident(&mut self) -> Token {
         let start = self.cursor.pos();
         let _ = self.cursor.advance();
-        self.cursor.eat_while(is_ident_continue);
+        self.ident_tail();
         self.make_token(start, TokenKind::Ident)
     }
 
+    fn ident_tail(&mut self) {
+        let dollar = self.options.dollar_idents;
+        self.cursor
+            .eat_while(|byte| is_ident_continue(byte) || (dollar && byte == b'$'));
+    }
+
+    /// Scans a pp-number (C11 §6.4.8): a digit or `.digit`, then any run of
+    /// digits, identifier letters, `.`, and `e`/`E`/`p`/`P` followed by a
+    /// sign. The grammar is deliberately loose, so `1.2.3`, `1..2`, `123abc`
+    /// and `0x1e+2` are each one token; a sign ends the number unless an
+    /// exponent letter precedes it, and a `'` ends it unless
+    /// `digit_separators` is set and a digit or letter follows. A `.` not
+    /// followed by a digit never gets here: it is a `Dot` or part of an
+    /// `Ellipsis`.
     fn pp_number(&mut self) -> Token {
         let start = self.cursor.pos();
+        let dollar = self.options.dollar_idents;
+        let separators = self.options.digit_separators;
 
-        let _ = self.cursor.advance();
+        let digit_next = self.cursor.peek_next().is_some_and(|b| b.is_ascii_digit());
+        match self.cursor.advance() {
+            Some(b'.') => debug_assert!(digit_next, "pp-number needs a digit after its dot"),
+            first => debug_assert!(first.is_some_and(|b| b.is_ascii_digit())),
+        }
 
         loop {
             match self.cursor.peek() {
-                Some(byte @ (b'e' | b'E' | b'p' | b'P'))
+                Some(b'e' | b'E' | b'p' | b'P')
                     if self
                         .cursor
                         .peek_next()
@@ -217,9 +474,22 @@ impl<'src> Lexer<'src> {
                 {
                     let _ = self.cursor.advance();
                     let _ = self.cursor.advance();
-                    let _ = byte;
                 }
-                Some(byte) if byte.is_ascii_digit() || is_ident_nondigit(byte) || byte == b'.' => {
+                Some(b'\'')
+                    if separators
+                        && self
+                            .cursor
+                            .peek_next()
+                            .is_some_and(|next| next.is_ascii_digit() || is_ident_nondigit(next)) =>
+                {
+                    let _ = self.cursor.advance();
+                }
+                Some(byte)
+                    if byte.is_ascii_digit()
+                        || is_ident_nondigit(byte)
+                        || byte == b'.'
+                        || (dollar && byte == b'$') =>
+                {
                     let _ = self.cursor.advance();
                 }
                 _ => break,
@@ -230,45 +500,37 @@ impl<'src> Lexer<'src> {
     }
 
     fn string_literal(&mut self, prefix_len: u32) -> Token {
-        let start = self.cursor.pos().saturating_sub(prefix_len as usize + 1);
-
-        while let Some(byte) = self.cursor.advance() {
-            match byte {
-                b'\\' => {
-                    let _ = self.cursor.advance();
-                }
-                b'"' => {
-                    return self.make_token(start, TokenKind::StringLiteral);
-                }
-                b'\n' => {
-                    return self.make_token(start, TokenKind::Unknown);
-                }
-                _ => {}
-            }
-        }
-
-        self.make_token(start, TokenKind::Unknown)
+        self.quoted(prefix_len, b'"', TokenKind::StringLiteral)
     }
 
     fn char_const(&mut self, prefix_len: u32) -> Token {
-        let start = self.cursor.pos().saturating_sub(prefix_len as usize + 1);
+        self.quoted(prefix_len, b'\'', TokenKind::CharConst)
+    }
 
-        while let Some(byte) = self.cursor.advance() {
-            match byte {
-                b'\\' => {
-                    let _ = self.cursor.advance();
-                }
-                b'\'' => {
-                    return self.make_token(start, TokenKind::CharConst);
-                }
-                b'\n' => {
-                    return self.make_token(start, TokenKind::Unknown);
-                }
-                _ => {}
+    /// Scans the rest of a literal whose opening quote and `prefix_len`
+    /// prefix bytes were consumed. A literal missing its `close` quote
+    /// before the end of the line is an error and lexes as one `Unknown`
+    /// token up to the newline, which is left for the next token.
+    fn quoted(&mut self, prefix_len: u32, close: u8, kind: TokenKind) -> Token {
+        let start = self.cursor.pos().saturating_sub(prefix_len as usize + 1);
+        while let Some(byte) = self.cursor.peek() {
+            if byte == b'\n' {
+                break;
+            }
+            let _ = self.cursor.advance();
+            if byte == close {
+                return self.make_token(start, kind);
+            }
+            if byte == b'\\' {
+                let _ = self.cursor.advance();
             }
         }
-
-        self.make_token(start, TokenKind::Unknown)
+        let token = self.make_token(start, TokenKind::Unknown);
+        self.diagnostics.push(Diagnostic::error(
+            format!("missing terminating {} character", char::from(close)),
+            token.span,
+        ));
+        token
     }
 
     fn punctuator(&mut self, start: usize, first: u8) -> Token {
@@ -290,7 +552,9 @@ impl<'src> Lexer<'src> {
             b';' => TokenKind::Semi,
             b':' => TokenKind::Colon,
             b'.' => {
-                if self.cursor.eat(b'.') && self.cursor.eat(b'.') {
+                if self.cursor.peek_at(0) == Some(b'.') && self.cursor.peek_at(1) == Some(b'.') {
+                    let _ = self.cursor.advance();
+                    let _ = self.cursor.advance();
                     TokenKind::Ellipsis
                 } else {
                     TokenKind::Dot
@@ -404,12 +668,42 @@ impl<'src> Lexer<'src> {
                 }
             }
             b'~' => TokenKind::Tilde,
+            b'@' | b'$' | b'`' => {
+                let span = Span::new(self.file, start as u32, self.pos32());
+                self.diagnostics.push(Diagnostic::error(
+                    format!("stray '{}' in program", char::from(first)),
+                    span,
+                ));
+                TokenKind::Unknown
+            }
             _ => TokenKind::Unknown,
         };
 
         self.make_token(start, kind)
     }
 
+    /// A run of bytes `>= 0x80` (Latin-1 or UTF-8 text outside comments and
+    /// literals) as one `Unknown` token with one "stray" error, spelled with
+    /// GCC's octal escapes.
+    fn non_ascii_run(&mut self) -> Token {
+        let start = self.cursor.pos();
+        while self.cursor.peek().is_some_and(|byte| !byte.is_ascii()) {
+            let _ = self.cursor.advance();
+        }
+        let token = self.make_token(start, TokenKind::Unknown);
+        let escaped: String = token
+            .span
+            .as_bytes(self.src)
+            .iter()
+            .map(|byte| format!("\\{byte:o}"))
+            .collect();
+        self.diagnostics.push(Diagnostic::error(
+            format!("stray '{escaped}' in program"),
+            token.span,
+        ));
+        token
+    }
+
     fn make_token(&self, start: usize, kind: TokenKind) -> Token {
         debug_assert!(self.cursor.pos() <= self.src.len());
         Token::new(
@@ -421,13 +715,26 @@ impl<'src> Lexer<'src> {
     fn pos32(&self) -> u32 {
         self.cursor.pos() as u32
     }
+}
+
+/// Yields every token up to and including the first `Eof`, then `None`.
+impl Iterator for Lexer<'_> {
+    type Item = Token;
 
-    #[cfg(test)]
-    fn slice(&self, token: Token) -> &'src [u8] {
-        &self.src[token.span.start() as usize..token.span.end() as usize]
+    fn next(&mut self) -> Option<Token> {
+        if self.finished {
+            return None;
+        }
+        let token = self.next_token();
+        if token.kind == TokenKind::Eof {
+            self.finished = true;
+        }
+        Some(token)
     }
 }
 
+impl FusedIterator for Lexer<'_> {}
+
 fn is_whitespace_no_newline(byte: u8) -> bool {
     matches!(byte, b' ' | b'\t' | b'\r' | 0x0B | 0x0C)
 }
@@ -446,8 +753,11 @@ fn is_ident_continue(byte: u8) -> bool {
 
 #[cfg(test)]
 mod tests {
-    use super::Lexer;
+    use super::{is_ident_start, is_whitespace_no_newline, LexOptions, Lexer};
+    use crate::char_class::{CharClass, CHAR_CLASS};
+    use slopcc_arena::intern::Interner;
     use crate::TokenKind;
+    use slopcc_common::diag::Severity;
     use slopcc_common::source::FileId;
     use slopcc_common::span::Span;
 
@@ -504,6 +814,57 @@ mod tests {
         );
     }
 
+    #[test]
+    fn comment_text_includes_delimiters() {
+        let mut lexer = Lexer::new(b"a /* keep me */ b // tail", fid());
+        let comments: Vec<_> = lexer
+            .by_ref()
+            .filter(|token| token.kind == TokenKind::Comment)
+            .collect();
+        assert_eq!(comments.len(), 2);
+        assert_eq!(comments[0].span, Span::new(fid(), 2, 15));
+        assert_eq!(lexer.text(comments[0]), b"/* keep me */");
+        assert_eq!(lexer.text(comments[1]), b"// tail");
+    }
+
+    #[test]
+    fn comments_lex_as_whitespace_unless_preserved() {
+        let options = LexOptions {
+            preserve_comments: false,
+            ..LexOptions::default()
+        };
+        let tokens: Vec<_> = Lexer::with_options(b"a/* c */b", fid(), options).collect();
+        assert_eq!(tokens[1].kind, TokenKind::Whitespace);
+        assert_eq!(tokens[1].span, Span::new(fid(), 1, 8));
+        assert!(tokens[2].leading_ws);
+    }
+
+    #[test]
+    fn line_comment_stops_before_crlf() {
+        let tokens = Lexer::tokenize(b"// x\r\ny", fid());
+        let kinds: Vec<_> = tokens.iter().map(|token| token.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                TokenKind::Comment,
+                TokenKind::Whitespace,
+                TokenKind::Newline,
+                TokenKind::Ident,
+                TokenKind::Eof,
+            ]
+        );
+        assert_eq!(tokens[0].span, Span::new(fid(), 0, 4));
+        assert_eq!(tokens[1].span, Span::new(fid(), 4, 5));
+        assert_eq!(tokens[2].span, Span::new(fid(), 5, 6));
+    }
+
+    #[test]
+    fn line_comment_keeps_lone_cr() {
+        let tokens = Lexer::tokenize(b"// a\rb\n", fid());
+        assert_eq!(tokens[0].kind, TokenKind::Comment);
+        assert_eq!(tokens[0].span, Span::new(fid(), 0, 6));
+    }
+
     #[test]
     fn lexes_unterminated_block_comment_as_comment() {
         let tokens = Lexer::tokenize(b"/* not closed", fid());
@@ -661,6 +1022,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn leading_dot_is_a_number_only_before_a_digit() {
+        use TokenKind::{Dot, Ellipsis, Eof, PpNumber, Whitespace};
+        assert_eq!(kinds(b".5"), vec![PpNumber, Eof]);
+        assert_eq!(kinds(b". 5"), vec![Dot, Whitespace, PpNumber, Eof]);
+        assert_eq!(kinds(b"."), vec![Dot, Eof]);
+        assert_eq!(kinds(b".x"), vec![Dot, TokenKind::Ident, Eof]);
+        assert_eq!(kinds(b"..."), vec![Ellipsis, Eof]);
+        assert_eq!(kinds(b"...5"), vec![Ellipsis, PpNumber, Eof]);
+    }
+
+    #[test]
+    fn two_dots_are_two_dot_tokens() {
+        let tokens = Lexer::tokenize(b"..", fid());
+        assert_eq!(tokens[0].kind, TokenKind::Dot);
+        assert_eq!(tokens[0].span, Span::new(fid(), 0, 1));
+        assert_eq!(tokens[1].kind, TokenKind::Dot);
+        assert_eq!(tokens[1].span, Span::new(fid(), 1, 2));
+        assert_eq!(kinds(b"...."), vec![TokenKind::Ellipsis, TokenKind::Dot, TokenKind::Eof]);
+    }
+
     #[test]
     fn lexes_header_names() {
         let mut angle = Lexer::new(b"<stdio.h>", fid());
@@ -670,6 +1052,89 @@ mod tests {
         assert_eq!(quote.lex_header_name().kind, TokenKind::HeaderName);
     }
 
+    #[test]
+    fn unterminated_angle_header_name_is_an_error() {
+        let mut lexer = Lexer::new(b"<stdio.h\n", fid());
+        let name = lexer.lex_header_name();
+        assert_eq!((name.kind, name.span), (TokenKind::Unknown, Span::new(fid(), 0, 8)));
+        assert_eq!(lexer.next_token().kind, TokenKind::Newline);
+        let diagnostics: Vec<_> = lexer.diagnostics().iter().collect();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].message, "missing terminating > character");
+        assert_eq!(diagnostics[0].span, Some(name.span));
+    }
+
+    #[test]
+    fn unterminated_quoted_header_name_is_an_error() {
+        let mut lexer = Lexer::new(b"\"my.h", fid());
+        let name = lexer.lex_header_name();
+        assert_eq!((name.kind, name.span), (TokenKind::Unknown, Span::new(fid(), 0, 5)));
+        assert_eq!(lexer.next_token().kind, TokenKind::Eof);
+        let diagnostics: Vec<_> = lexer.diagnostics().iter().collect();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].message, "missing terminating \" character");
+    }
+
+    #[test]
+    fn non_header_name_lexes_as_ordinary_tokens() {
+        let mut lexer = Lexer::new(b"CONFIG_H\n", fid());
+        let name = lexer.lex_header_name();
+        assert_eq!((name.kind, lexer.text(name)), (TokenKind::Ident, &b"CONFIG_H"[..]));
+        assert_eq!(lexer.lex_header_name().kind, TokenKind::Newline);
+    }
+
+    #[test]
+    fn header_name_mode_covers_one_token() {
+        let mut lexer = Lexer::new(b"include <stdio.h> <x.h>\n", fid());
+        assert_eq!(lexer.next_token().kind, TokenKind::Ident);
+        lexer.expect_header_name();
+        assert_eq!(lexer.next_token().kind, TokenKind::Whitespace);
+        let name = lexer.next_token();
+        assert_eq!((name.kind, lexer.text(name)), (TokenKind::HeaderName, &b"<stdio.h>"[..]));
+        assert_eq!(lexer.next_token().kind, TokenKind::Whitespace);
+        assert_eq!(lexer.next_token().kind, TokenKind::Lt);
+
+        let mut lexer = Lexer::new(b"NAME <x.h>", fid());
+        lexer.expect_header_name();
+        assert_eq!(lexer.next_token().kind, TokenKind::Ident);
+        assert_eq!(lexer.next_significant().kind, TokenKind::Lt);
+
+        let mut lexer = Lexer::new(b"\n<x.h>\n", fid());
+        lexer.expect_header_name();
+        assert_eq!(lexer.next_token().kind, TokenKind::Newline);
+        assert_eq!(lexer.next_token().kind, TokenKind::Lt);
+    }
+
+    fn directive_line(src: &[u8]) -> (Vec<&[u8]>, TokenKind) {
+        let mut lexer = Lexer::new(src, fid());
+        let hash = lexer.next_significant();
+        assert!(hash.kind == TokenKind::Hash && hash.bol);
+        let tokens = lexer.lex_directive_line();
+        let texts = tokens.iter().map(|&token| lexer.text(token)).collect();
+        (texts, lexer.next_token().kind)
+    }
+
+    #[test]
+    fn directive_line_stops_before_its_newline() {
+        let (texts, next) = directive_line(b"#define FOO 1\nint x;\n");
+        assert_eq!(texts, [&b"define"[..], b"FOO", b"1"]);
+        assert_eq!(next, TokenKind::Newline);
+
+        let (texts, next) = directive_line(b"# /* c */ endif");
+        assert_eq!(texts, [&b"endif"[..]]);
+        assert_eq!(next, TokenKind::Eof);
+    }
+
+    #[test]
+    fn directive_line_follows_continuations() {
+        let (texts, next) = directive_line(b"#define BAR \\\n 2\nBAR\n");
+        assert_eq!(texts, [&b"define"[..], b"BAR", b"2"]);
+        assert_eq!(next, TokenKind::Newline);
+
+        let (texts, _) = directive_line(b"#if A \\\r\n|| B\r\n");
+        assert_eq!(texts, [&b"if"[..], b"A", b"||", b"B"]);
+    }
+
     #[test]
     fn lexes_full_stream_with_spans() {
         let src = b"int main() { return 0; }";
@@ -700,6 +1165,26 @@ mod tests {
         assert_eq!(tokens[10].span, Span::new(fid(), 20, 21));
     }
 
+    #[test]
+    fn non_ascii_run_is_one_token_and_one_error() {
+        let src = b"a\xE9\xE9\xE9b";
+        let mut lexer = Lexer::new(src, fid());
+        let tokens: Vec<_> = lexer.by_ref().map(|token| (token.kind, token.span)).collect();
+        assert_eq!(
+            tokens,
+            vec![
+                (TokenKind::Ident, Span::new(fid(), 0, 1)),
+                (TokenKind::Unknown, Span::new(fid(), 1, 4)),
+                (TokenKind::Ident, Span::new(fid(), 4, 5)),
+                (TokenKind::Eof, Span::at(fid(), 5)),
+            ]
+        );
+        let diagnostics: Vec<_> = lexer.diagnostics().iter().collect();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].message, "stray '\\351\\351\\351' in program");
+        assert_eq!(diagnostics[0].span, Some(Span::new(fid(), 1, 4)));
+    }
+
     #[test]
     fn lexes_unknown_bytes() {
         assert_eq!(
@@ -708,6 +1193,295 @@ mod tests {
         );
     }
 
+    #[test]
+    fn trailing_backslash_is_single_unknown_token() {
+        let mut lexer = Lexer::new(b"a\\", fid());
+        let ident = lexer.next_token();
+        let backslash = lexer.next_token();
+        let eof = lexer.next_token();
+        assert_eq!(ident.kind, TokenKind::Ident);
+        assert_eq!(backslash.kind, TokenKind::Unknown);
+        assert_eq!(backslash.span, Span::new(fid(), 1, 2));
+        assert_eq!(lexer.text(backslash), b"\\");
+        assert_eq!(eof.kind, TokenKind::Eof);
+        assert_eq!(eof.span, Span::at(fid(), 2));
+    }
+
+    #[test]
+    fn skips_leading_utf8_bom() {
+        let mut lexer = Lexer::new("\u{FEFF}int".as_bytes(), fid());
+        let token = lexer.next_token();
+        assert_eq!(token.kind, TokenKind::Ident);
+        assert_eq!(token.span, Span::new(fid(), 3, 6));
+        assert_eq!(lexer.text(token), b"int");
+        assert!(token.bol);
+        assert!(!token.leading_ws);
+    }
+
+    #[test]
+    fn bom_only_input_is_eof() {
+        assert_eq!(kinds(b"\xEF\xBB\xBF"), vec![TokenKind::Eof]);
+    }
+
+    #[test]
+    fn diagnoses_embedded_nul_and_continues() {
+        let mut lexer = Lexer::new(b"a\0b", fid());
+        let tokens: Vec<_> = lexer.by_ref().collect();
+        let kinds: Vec<_> = tokens.iter().map(|token| token.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                TokenKind::Ident,
+                TokenKind::Unknown,
+                TokenKind::Ident,
+                TokenKind::Eof,
+            ]
+        );
+        let diagnostics = lexer.take_diagnostics();
+        assert_eq!(diagnostics.len(), 1);
+        let diagnostic = diagnostics.iter().next().unwrap();
+        assert_eq!(diagnostic.severity, Severity::Error);
+        assert_eq!(diagnostic.message, "null character in source");
+        assert_eq!(diagnostic.span, Some(Span::new(fid(), 1, 2)));
+        assert!(lexer.diagnostics().is_empty());
+    }
+
+    #[test]
+    fn warns_on_nested_comment_opener() {
+        let mut lexer = Lexer::new(b"/* a /* b */ c", fid());
+        let tokens: Vec<_> = lexer.by_ref().collect();
+        assert_eq!(tokens[0].kind, TokenKind::Comment);
+        assert_eq!(tokens[0].span, Span::new(fid(), 0, 12));
+        let diagnostics = lexer.take_diagnostics();
+        assert_eq!(diagnostics.len(), 1);
+        let diagnostic = diagnostics.iter().next().unwrap();
+        assert_eq!(diagnostic.severity, Severity::Warning);
+        assert_eq!(diagnostic.code, Some("comment"));
+        assert_eq!(diagnostic.span, Some(Span::new(fid(), 5, 7)));
+    }
+
+    #[test]
+    fn c90_flags_line_comments_as_pedantic() {
+        let mut lexer = Lexer::new(b"a // b\n", fid());
+        let _: Vec<_> = lexer.by_ref().collect();
+        assert!(lexer.diagnostics().is_empty());
+
+        let options = LexOptions {
+            c90: true,
+            ..LexOptions::default()
+        };
+        let mut lexer = Lexer::with_options(b"a // b\n/* c */", fid(), options);
+        let _: Vec<_> = lexer.by_ref().collect();
+        let diagnostics = lexer.take_diagnostics();
+        assert_eq!(diagnostics.len(), 1);
+        let diagnostic = diagnostics.iter().next().unwrap();
+        assert_eq!(diagnostic.severity, Severity::Warning);
+        assert_eq!(diagnostic.code, Some("pedantic"));
+        assert_eq!(diagnostic.span, Some(Span::new(fid(), 2, 4)));
+    }
+
+    #[test]
+    fn stray_characters_are_diagnosed() {
+        let mut lexer = Lexer::new(b"a @ $b`", fid());
+        let tokens: Vec<_> = lexer.by_ref().filter(|t| !t.kind.is_trivia()).collect();
+        let kinds: Vec<_> = tokens.iter().map(|t| t.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                TokenKind::Ident,
+                TokenKind::Unknown,
+                TokenKind::Unknown,
+                TokenKind::Ident,
+                TokenKind::Unknown,
+                TokenKind::Eof,
+            ]
+        );
+        let diagnostics = lexer.take_diagnostics();
+        let found: Vec<_> = diagnostics
+            .iter()
+            .map(|d| (d.severity, d.message.as_str(), d.span))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (Severity::Error, "stray '@' in program", Some(Span::new(fid(), 2, 3))),
+                (Severity::Error, "stray '$' in program", Some(Span::new(fid(), 4, 5))),
+                (Severity::Error, "stray '`' in program", Some(Span::new(fid(), 6, 7))),
+            ]
+        );
+    }
+
+    #[test]
+    fn dollar_is_an_identifier_byte_in_gnu_mode() {
+        let options = LexOptions {
+            dollar_idents: true,
+            ..LexOptions::default()
+        };
+        let src = b"$a b$c 1$";
+        let mut lexer = Lexer::with_options(src, fid(), options);
+        let tokens: Vec<_> = lexer.by_ref().filter(|t| !t.kind.is_trivia()).collect();
+        let texts: Vec<_> = tokens.iter().map(|t| (t.kind, t.span.as_bytes(src))).collect();
+        assert_eq!(
+            texts,
+            vec![
+                (TokenKind::Ident, &b"$a"[..]),
+                (TokenKind::Ident, &b"b$c"[..]),
+                (TokenKind::PpNumber, &b"1$"[..]),
+                (TokenKind::Eof, &b""[..]),
+            ]
+        );
+        assert!(lexer.diagnostics().is_empty());
+    }
+
+    #[test]
+    fn digit_separators_join_pp_numbers_only_when_enabled() {
+        let separated = |src: &'static [u8], enabled| {
+            let options = LexOptions {
+                digit_separators: enabled,
+                ..LexOptions::default()
+            };
+            let mut lexer = Lexer::with_options(src, fid(), options);
+            let texts: Vec<_> = lexer
+                .by_ref()
+                .filter(|t| !t.kind.is_eof())
+                .map(|t| (t.kind, t.span.as_bytes(src)))
+                .collect();
+            (texts, lexer.diagnostics().len())
+        };
+        assert_eq!(
+            separated(b"1'000", true),
+            (vec![(TokenKind::PpNumber, &b"1'000"[..])], 0)
+        );
+        assert_eq!(
+            separated(b"0x1'ff'", true),
+            (
+                vec![
+                    (TokenKind::PpNumber, &b"0x1'ff"[..]),
+                    (TokenKind::Unknown, &b"'"[..]),
+                ],
+                1
+            )
+        );
+        assert_eq!(
+            separated(b"1'000", false),
+            (
+                vec![
+                    (TokenKind::PpNumber, &b"1"[..]),
+                    (TokenKind::Unknown, &b"'000"[..]),
+                ],
+                1
+            )
+        );
+        assert_eq!(
+            separated(b"1'0'", false),
+            (
+                vec![
+                    (TokenKind::PpNumber, &b"1"[..]),
+                    (TokenKind::CharConst, &b"'0'"[..]),
+                ],
+                0
+            )
+        );
+    }
+
+    #[test]
+    fn skip_to_newline_resumes_at_the_line_end() {
+        let mut lexer = Lexer::new(b"#bad \"x y\nint", fid());
+        assert_eq!(lexer.next_token().kind, TokenKind::Hash);
+        lexer.skip_to_newline();
+        assert_eq!(lexer.next_token().kind, TokenKind::Newline);
+        let next = lexer.next_token();
+        assert_eq!((next.kind, next.bol), (TokenKind::Ident, true));
+        lexer.skip_to_newline();
+        assert_eq!(lexer.next_token().kind, TokenKind::Eof);
+        assert!(lexer.diagnostics().is_empty());
+    }
+
+    #[test]
+    fn interner_gives_equal_spellings_one_symbol() {
+        let mut interner = Interner::new();
+        let tokens: Vec<_> = Lexer::new(b"foo foo bar 1 +", fid())
+            .with_interner(&mut interner)
+            .filter(|t| !t.kind.is_trivia())
+            .collect();
+        let symbols: Vec<_> = tokens.iter().map(|t| t.symbol).collect();
+        assert_eq!(symbols[0], symbols[1]);
+        assert_ne!(symbols[0], symbols[2]);
+        assert_eq!(interner.resolve(symbols[2].unwrap()), "bar");
+        assert_eq!(interner.resolve(symbols[3].unwrap()), "1");
+        assert_eq!(symbols[4..], [None, None]);
+        assert!(Lexer::new(b"foo", fid()).next_token().symbol.is_none());
+    }
+
+    #[test]
+    fn collapse_trivia_merges_a_run_into_one_token() {
+        let src = b"a   /*c*/  \tb /*x*/\n";
+        let collapsed = |preserve_comments| {
+            let options = LexOptions {
+                collapse_trivia: true,
+                preserve_comments,
+                ..LexOptions::default()
+            };
+            Lexer::with_options(src, fid(), options)
+                .map(|t| (t.kind, t.span))
+                .collect::<Vec<_>>()
+        };
+        let span = |start, end| Span::new(fid(), start, end);
+        assert_eq!(
+            collapsed(true),
+            vec![
+                (TokenKind::Ident, span(0, 1)),
+                (TokenKind::Comment, span(1, 12)),
+                (TokenKind::Ident, span(12, 13)),
+                (TokenKind::Comment, span(13, 19)),
+                (TokenKind::Newline, span(19, 20)),
+                (TokenKind::Eof, span(20, 20)),
+            ]
+        );
+        let kinds: Vec<_> = collapsed(false).into_iter().map(|(kind, _)| kind).collect();
+        assert_eq!(kinds[1], TokenKind::Whitespace);
+        assert_eq!(kinds[3], TokenKind::Whitespace);
+
+        let options = LexOptions {
+            collapse_trivia: true,
+            ..LexOptions::default()
+        };
+        let tokens: Vec<_> = Lexer::with_options(b"   /*c*/  ", fid(), options).collect();
+        assert_eq!(tokens.len(), 2);
+        assert_eq!(tokens[0].span, Span::new(fid(), 0, 10));
+        assert_eq!(Lexer::tokenize(b"  /*c*/ ", fid()).len(), 4);
+    }
+
+    #[test]
+    fn u8_char_constants_need_c23() {
+        let src = b"u8'x'";
+        let mut lexer = Lexer::new(src, fid());
+        let tokens: Vec<_> = lexer.by_ref().map(|t| (t.kind, t.span.as_bytes(src))).collect();
+        assert_eq!(
+            tokens,
+            vec![
+                (TokenKind::Ident, &b"u8"[..]),
+                (TokenKind::CharConst, &b"'x'"[..]),
+                (TokenKind::Eof, &b""[..]),
+            ]
+        );
+        let diagnostics = lexer.take_diagnostics();
+        let diagnostic = diagnostics.iter().next().unwrap();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostic.severity, Severity::Warning);
+        assert_eq!(diagnostic.span, Some(Span::new(fid(), 0, 3)));
+
+        let options = LexOptions {
+            u8_char_consts: true,
+            ..LexOptions::default()
+        };
+        let mut lexer = Lexer::with_options(src, fid(), options);
+        let token = lexer.next_token();
+        assert_eq!((token.kind, token.span), (TokenKind::CharConst, Span::new(fid(), 0, 5)));
+        assert!(lexer.diagnostics().is_empty());
+        assert_eq!(kinds(b"u8x"), vec![TokenKind::Ident, TokenKind::Eof]);
+    }
+
     #[test]
     fn lexes_empty_input() {
         assert_eq!(kinds(b""), vec![TokenKind::Eof]);
@@ -755,17 +1529,270 @@ mod tests {
         assert_eq!(kinds(b"'x"), vec![TokenKind::Unknown, TokenKind::Eof]);
     }
 
+    #[test]
+    fn raw_newline_ends_literal_with_an_error() {
+        let src = b"\"ab\ncd\"\n'x\ny";
+        let mut lexer = Lexer::new(src, fid());
+        let tokens: Vec<_> = lexer.by_ref().map(|token| (token.kind, token.span)).collect();
+        let span = |start, end| Span::new(fid(), start, end);
+        assert_eq!(
+            tokens,
+            vec![
+                (TokenKind::Unknown, span(0, 3)),
+                (TokenKind::Newline, span(3, 4)),
+                (TokenKind::Ident, span(4, 6)),
+                (TokenKind::Unknown, span(6, 7)),
+                (TokenKind::Newline, span(7, 8)),
+                (TokenKind::Unknown, span(8, 10)),
+                (TokenKind::Newline, span(10, 11)),
+                (TokenKind::Ident, span(11, 12)),
+                (TokenKind::Eof, span(12, 12)),
+            ]
+        );
+        let diagnostics: Vec<_> = lexer
+            .diagnostics()
+            .iter()
+            .map(|diagnostic| (diagnostic.message.as_str(), diagnostic.span))
+            .collect();
+        assert_eq!(
+            diagnostics,
+            [
+                ("missing terminating \" character", Some(span(0, 3))),
+                ("missing terminating \" character", Some(span(6, 7))),
+                ("missing terminating ' character", Some(span(8, 10))),
+            ]
+        );
+    }
+
+    #[test]
+    fn escaped_newline_continues_a_literal() {
+        assert_eq!(kinds(b"\"a\\\nb\""), vec![TokenKind::StringLiteral, TokenKind::Eof]);
+    }
+
+    #[test]
+    fn iterator_matches_tokenize() {
+        let src = b"int x = a->b + 1; // done\n";
+        let streamed: Vec<_> = Lexer::new(src, fid()).collect();
+        assert_eq!(streamed, Lexer::tokenize(src, fid()));
+    }
+
+    #[test]
+    fn iterator_fuses_after_eof() {
+        let mut lexer = Lexer::new(b"x", fid());
+        assert_eq!(lexer.next().map(|token| token.kind), Some(TokenKind::Ident));
+        assert_eq!(lexer.next().map(|token| token.kind), Some(TokenKind::Eof));
+        assert_eq!(lexer.next(), None);
+        assert_eq!(lexer.next(), None);
+    }
+
+    #[test]
+    fn marks_tokens_preceded_by_whitespace() {
+        let tokens = Lexer::tokenize(b"a b", fid());
+        assert!(!tokens[0].leading_ws);
+        assert_eq!(tokens[2].kind, TokenKind::Ident);
+        assert!(tokens[2].leading_ws);
+    }
+
+    #[test]
+    fn adjacent_tokens_have_no_leading_whitespace() {
+        let tokens = Lexer::tokenize(b"a+b", fid());
+        assert_eq!(tokens[1].kind, TokenKind::Plus);
+        assert!(!tokens[1].leading_ws);
+        assert!(!tokens[2].leading_ws);
+    }
+
+    #[test]
+    fn comments_and_newlines_count_as_leading_whitespace() {
+        let tokens = Lexer::tokenize(b"a/**/b\nc", fid());
+        assert_eq!(tokens[2].kind, TokenKind::Ident);
+        assert!(tokens[2].leading_ws);
+        assert_eq!(tokens[4].kind, TokenKind::Ident);
+        assert!(tokens[4].leading_ws);
+    }
+
+    #[test]
+    fn hash_at_line_start_is_bol() {
+        let tokens = Lexer::tokenize(b"#a\n   #b", fid());
+        assert_eq!(tokens[0].kind, TokenKind::Hash);
+        assert!(tokens[0].bol);
+        assert!(!tokens[1].bol);
+        assert_eq!(tokens[4].kind, TokenKind::Hash);
+        assert!(tokens[4].bol);
+    }
+
+    #[test]
+    fn hash_after_other_tokens_is_not_bol() {
+        let tokens = Lexer::tokenize(b"a # b", fid());
+        assert!(tokens[0].bol);
+        assert_eq!(tokens[2].kind, TokenKind::Hash);
+        assert!(!tokens[2].bol);
+    }
+
+    #[test]
+    fn comments_do_not_clear_bol() {
+        let tokens = Lexer::tokenize(b"/* c */ #", fid());
+        assert_eq!(tokens[2].kind, TokenKind::Hash);
+        assert!(tokens[2].bol);
+    }
+
+    #[test]
+    fn text_returns_token_spelling() {
+        let mut lexer = Lexer::new(b"foo 42", fid());
+        let ident = lexer.next_token();
+        let _ = lexer.next_token();
+        let number = lexer.next_token();
+        assert_eq!(lexer.text(ident), b"foo");
+        assert_eq!(lexer.text(number), b"42");
+    }
+
+    #[test]
+    fn next_significant_skips_trivia() {
+        let mut lexer = Lexer::new(b"  /*c*/ foo\n// x\n bar", fid());
+        let first = lexer.next_significant();
+        assert_eq!(first.kind, TokenKind::Ident);
+        assert_eq!(lexer.text(first), b"foo");
+        let second = lexer.next_significant();
+        assert_eq!(lexer.text(second), b"bar");
+        assert_eq!(lexer.next_significant().kind, TokenKind::Eof);
+    }
+
+    #[test]
+    fn next_significant_can_keep_newlines() {
+        let options = LexOptions {
+            significant_newlines: true,
+            ..LexOptions::default()
+        };
+        let mut lexer = Lexer::with_options(b"a /* c */\nb", fid(), options);
+        assert_eq!(lexer.next_significant().kind, TokenKind::Ident);
+        assert_eq!(lexer.next_significant().kind, TokenKind::Newline);
+        assert_eq!(lexer.next_significant().kind, TokenKind::Ident);
+    }
+
     #[test]
     fn pp_numbers_greedy_sign_exponents() {
         let mut lexer = Lexer::new(b"0x1p+3 1e-2", fid());
         let first = lexer.next_token();
         assert_eq!(first.kind, TokenKind::PpNumber);
-        assert_eq!(lexer.slice(first), b"0x1p+3");
+        assert_eq!(lexer.text(first), b"0x1p+3");
 
         let _ = lexer.next_token();
 
         let second = lexer.next_token();
         assert_eq!(second.kind, TokenKind::PpNumber);
-        assert_eq!(lexer.slice(second), b"1e-2");
+        assert_eq!(lexer.text(second), b"1e-2");
+    }
+
+    #[test]
+    fn pp_number_boundaries() {
+        let cases: [(&[u8], &[u8]); 11] = [
+            (b"1.2.3;", b"1.2.3"),
+            (b"1..2;", b"1..2"),
+            (b"0x1p;", b"0x1p"),
+            (b"0x1p", b"0x1p"),
+            (b"123abc+1", b"123abc"),
+            (b"1or2", b"1or2"),
+            (b"0xGg)", b"0xGg"),
+            (b"0x1e+2", b"0x1e+2"),
+            (b"1e+", b"1e+"),
+            (b"1+2", b"1"),
+            (b".5.e-x", b".5.e-x"),
+        ];
+        for (src, number) in cases {
+            let mut lexer = Lexer::new(src, fid());
+            let token = lexer.next_token();
+            assert_eq!(token.kind, TokenKind::PpNumber, "{src:?}");
+            assert_eq!(lexer.text(token), number, "{src:?}");
+        }
+    }
+
+    /// The comparison chain `scan` dispatched on before `CHAR_CLASS`.
+    fn reference_class(byte: u8) -> CharClass {
+        if is_whitespace_no_newline(byte) {
+            CharClass::Whitespace
+        } else if byte == b'\n' {
+            CharClass::Newline
+        } else if byte == 0 {
+            CharClass::Nul
+        } else if byte == b'/' {
+            CharClass::Slash
+        } else if byte.is_ascii_digit() {
+            CharClass::Digit
+        } else if byte == b'.' {
+            CharClass::Dot
+        } else if matches!(byte, b'L' | b'u' | b'U') {
+            CharClass::Prefix
+        } else if is_ident_start(byte) {
+            CharClass::IdentStart
+        } else if byte == b'$' {
+            CharClass::Dollar
+        } else if byte == b'"' {
+            CharClass::DoubleQuote
+        } else if byte == b'\'' {
+            CharClass::SingleQuote
+        } else if !byte.is_ascii() {
+            CharClass::NonAscii
+        } else {
+            CharClass::Other
+        }
+    }
+
+    #[test]
+    fn char_class_table_matches_byte_predicates() {
+        for byte in 0..=u8::MAX {
+            assert_eq!(CHAR_CLASS[usize::from(byte)], reference_class(byte), "byte {byte:#04x}");
+        }
+    }
+
+    #[test]
+    fn table_dispatch_lexes_mixed_corpus() {
+        let src: &[u8] =
+            b"int $x = u8\"s\" + L'c' /* c */ // d\n\t.5 .e a.b @`\\\x00 \xC3\xA9 _9;\r\n";
+        for dollar_idents in [false, true] {
+            let options = LexOptions {
+                dollar_idents,
+                ..LexOptions::default()
+            };
+            let mut lexer = Lexer::with_options(src, fid(), options);
+            let tokens: Vec<_> = lexer
+                .by_ref()
+                .filter(|token| token.kind != TokenKind::Whitespace)
+                .map(|token| (token.kind, token.span.as_bytes(src)))
+                .collect();
+            let dollar: &[(TokenKind, &[u8])] = if dollar_idents {
+                &[(TokenKind::Ident, b"$x")]
+            } else {
+                &[(TokenKind::Unknown, b"$"), (TokenKind::Ident, b"x")]
+            };
+            let rest: &[(TokenKind, &[u8])] = &[
+                (TokenKind::Assign, b"="),
+                (TokenKind::StringLiteral, b"u8\"s\""),
+                (TokenKind::Plus, b"+"),
+                (TokenKind::CharConst, b"L'c'"),
+                (TokenKind::Comment, b"/* c */"),
+                (TokenKind::Comment, b"// d"),
+                (TokenKind::Newline, b"\n"),
+                (TokenKind::PpNumber, b".5"),
+                (TokenKind::Dot, b"."),
+                (TokenKind::Ident, b"e"),
+                (TokenKind::Ident, b"a"),
+                (TokenKind::Dot, b"."),
+                (TokenKind::Ident, b"b"),
+                (TokenKind::Unknown, b"@"),
+                (TokenKind::Unknown, b"`"),
+                (TokenKind::Unknown, b"\\"),
+                (TokenKind::Unknown, b"\x00"),
+                (TokenKind::Unknown, b"\xC3\xA9"),
+                (TokenKind::Ident, b"_9"),
+                (TokenKind::Semi, b";"),
+                (TokenKind::Newline, b"\n"),
+                (TokenKind::Eof, b""),
+            ];
+            let expected: Vec<(TokenKind, &[u8])> = [(TokenKind::Ident, &b"int"[..])]
+                .into_iter()
+                .chain(dollar.iter().copied())
+                .chain(rest.iter().copied())
+                .collect();
+            assert_eq!(tokens, expected, "dollar_idents: {dollar_idents}");
+        }
     }
 }
@@ -1,8 +1,21 @@
+mod char_class;
 mod cursor;
+mod keyword;
 mod lexer;
+mod stats;
+mod stream;
 mod token;
 
-pub use lexer::Lexer;
+pub use keyword::{
+  keyword,
+  Keyword,
+};
+pub use lexer::{
+  LexOptions,
+  Lexer,
+};
+pub use stats::LexStats;
+pub use stream::TokenStream;
 pub use token::{
   Token,
   TokenKind,
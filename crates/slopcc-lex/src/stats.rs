@@ -0,0 +1,60 @@
+use crate::token::TokenKind;
+
+/// Token counts for one lexed buffer, for profiling and regression tests.
+/// `total` counts every token, the final `Eof` included, so it equals the
+/// length of the `tokenize` output; the other fields count by kind.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LexStats {
+    pub total: usize,
+    pub idents: usize,
+    pub pp_numbers: usize,
+    pub char_consts: usize,
+    pub string_literals: usize,
+    /// Punctuators, `#` and `##` included.
+    pub punctuators: usize,
+    /// Whitespace, comments and newlines.
+    pub trivia: usize,
+}
+
+impl LexStats {
+    /// Counts one token of `kind`.
+    pub fn record(&mut self, kind: TokenKind) {
+        self.total += 1;
+        let count = match kind {
+            TokenKind::Ident => &mut self.idents,
+            TokenKind::PpNumber => &mut self.pp_numbers,
+            TokenKind::CharConst => &mut self.char_consts,
+            TokenKind::StringLiteral => &mut self.string_literals,
+            _ if kind.is_punctuator() => &mut self.punctuators,
+            _ if kind.is_trivia() => &mut self.trivia,
+            _ => return,
+        };
+        *count += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LexStats;
+    use crate::Lexer;
+    use slopcc_common::prelude::FileId;
+
+    #[test]
+    fn counts_tokens_by_kind() {
+        let src = b"int x = 42; // answer\nputs(\"hi\", 'c', @);\n";
+        let (tokens, stats) = Lexer::tokenize_with_stats(src, FileId::new_for_tests(0));
+        assert_eq!(
+            stats,
+            LexStats {
+                total: 24,
+                idents: 3,
+                pp_numbers: 1,
+                char_consts: 1,
+                string_literals: 1,
+                punctuators: 7,
+                trivia: 9,
+            }
+        );
+        assert_eq!(stats.total, tokens.len());
+    }
+}
@@ -0,0 +1,91 @@
+use crate::{lexer::Lexer, token::Token};
+
+/// Significant-token stream with up to two tokens of lookahead.
+///
+/// Whitespace and comments are always skipped; newlines follow the wrapped
+/// lexer's `LexOptions::significant_newlines`. Once `Eof` is reached, every
+/// further `peek`/`bump` returns that same `Eof`.
+pub struct TokenStream<'src> {
+    lexer: Lexer<'src>,
+    lookahead: [Option<Token>; 2],
+}
+
+impl<'src> TokenStream<'src> {
+    #[must_use]
+    pub fn new(lexer: Lexer<'src>) -> Self {
+        Self {
+            lexer,
+            lookahead: [None, None],
+        }
+    }
+
+    /// Next token, without consuming it.
+    #[must_use]
+    pub fn peek(&mut self) -> Token {
+        self.fill(0)
+    }
+
+    /// Token after the next one, without consuming either.
+    #[must_use]
+    pub fn peek2(&mut self) -> Token {
+        let _ = self.fill(0);
+        self.fill(1)
+    }
+
+    /// Consumes and returns the next token.
+    pub fn bump(&mut self) -> Token {
+        let token = self.peek();
+        self.lookahead = [self.lookahead[1], None];
+        token
+    }
+
+    /// Source bytes spelled by `token`.
+    #[must_use]
+    pub fn text(&self, token: Token) -> &'src [u8] {
+        self.lexer.text(token)
+    }
+
+    fn fill(&mut self, slot: usize) -> Token {
+        let lexer = &mut self.lexer;
+        *self.lookahead[slot].get_or_insert_with(|| lexer.next_significant())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TokenStream;
+    use crate::{Lexer, TokenKind};
+    use slopcc_common::source::FileId;
+
+    fn stream(src: &[u8]) -> TokenStream<'_> {
+        TokenStream::new(Lexer::new(src, FileId::new_for_tests(0)))
+    }
+
+    #[test]
+    fn peek_does_not_advance() {
+        let mut tokens = stream(b"a + b");
+        let first = tokens.peek();
+        assert_eq!(tokens.peek(), first);
+        assert_eq!(tokens.text(first), b"a");
+    }
+
+    #[test]
+    fn peek_and_bump_agree() {
+        let mut tokens = stream(b"a + b");
+        let peeked = tokens.peek();
+        let second = tokens.peek2();
+        assert_eq!(second.kind, TokenKind::Plus);
+        assert_eq!(tokens.bump(), peeked);
+        assert_eq!(tokens.bump(), second);
+        assert_eq!(tokens.peek().kind, TokenKind::Ident);
+    }
+
+    #[test]
+    fn repeats_eof_at_end() {
+        let mut tokens = stream(b"x");
+        assert_eq!(tokens.bump().kind, TokenKind::Ident);
+        assert_eq!(tokens.peek2().kind, TokenKind::Eof);
+        assert_eq!(tokens.bump().kind, TokenKind::Eof);
+        assert_eq!(tokens.bump().kind, TokenKind::Eof);
+    }
+}
@@ -1,15 +1,31 @@
+use std::fmt;
+
+use slopcc_arena::intern::Symbol;
 use slopcc_common::span::Span;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Token {
     pub kind: TokenKind,
     pub span: Span,
+    /// The immediately preceding token was whitespace, a comment, or a newline.
+    pub leading_ws: bool,
+    /// Only whitespace or comments precede the token on its line.
+    pub bol: bool,
+    /// Interned spelling of an `Ident` or `PpNumber`, set when the lexer
+    /// has an interner (`Lexer::with_interner`).
+    pub symbol: Option<Symbol>,
 }
 
 impl Token {
     #[must_use]
     pub const fn new(kind: TokenKind, span: Span) -> Self {
-        Self { kind, span }
+        Self {
+            kind,
+            span,
+            leading_ws: false,
+            bol: false,
+            symbol: None,
+        }
     }
 }
 
@@ -75,9 +91,115 @@ pub enum TokenKind {
     Unknown,
 }
 
+impl TokenKind {
+    /// Fixed-spelling operators and separators, `#` and `##` included.
+    #[must_use]
+    pub const fn is_punctuator(self) -> bool {
+        self.spelling().is_some()
+    }
+
+    /// Numbers, character constants, and string literals.
+    #[must_use]
+    pub const fn is_literal(self) -> bool {
+        matches!(self, Self::PpNumber | Self::CharConst | Self::StringLiteral)
+    }
+
+    /// Whitespace, comments, and newlines.
+    #[must_use]
+    pub const fn is_trivia(self) -> bool {
+        matches!(self, Self::Whitespace | Self::Comment | Self::Newline)
+    }
+
+    #[must_use]
+    pub const fn is_eof(self) -> bool {
+        matches!(self, Self::Eof)
+    }
+
+    /// Exact source spelling of a punctuator; `None` for kinds whose text varies.
+    #[must_use]
+    pub const fn spelling(self) -> Option<&'static str> {
+        let text = match self {
+            Self::Hash => "#",
+            Self::HashHash => "##",
+            Self::LParen => "(",
+            Self::RParen => ")",
+            Self::LBracket => "[",
+            Self::RBracket => "]",
+            Self::LBrace => "{",
+            Self::RBrace => "}",
+            Self::Comma => ",",
+            Self::Semi => ";",
+            Self::Colon => ":",
+            Self::Ellipsis => "...",
+            Self::Dot => ".",
+            Self::Arrow => "->",
+            Self::Plus => "+",
+            Self::Minus => "-",
+            Self::Star => "*",
+            Self::Slash => "/",
+            Self::Percent => "%",
+            Self::PlusPlus => "++",
+            Self::MinusMinus => "--",
+            Self::Eq => "==",
+            Self::Ne => "!=",
+            Self::Lt => "<",
+            Self::Gt => ">",
+            Self::Le => "<=",
+            Self::Ge => ">=",
+            Self::And => "&&",
+            Self::Or => "||",
+            Self::Not => "!",
+            Self::Amp => "&",
+            Self::Pipe => "|",
+            Self::Caret => "^",
+            Self::Tilde => "~",
+            Self::Shl => "<<",
+            Self::Shr => ">>",
+            Self::Assign => "=",
+            Self::PlusAssign => "+=",
+            Self::MinusAssign => "-=",
+            Self::StarAssign => "*=",
+            Self::SlashAssign => "/=",
+            Self::PercentAssign => "%=",
+            Self::AmpAssign => "&=",
+            Self::PipeAssign => "|=",
+            Self::CaretAssign => "^=",
+            Self::ShlAssign => "<<=",
+            Self::ShrAssign => ">>=",
+            Self::Question => "?",
+            _ => return None,
+        };
+        Some(text)
+    }
+}
+
+/// Punctuators print their spelling; other kinds print a description
+/// suitable for "expected X, found Y" diagnostics.
+impl fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(text) = self.spelling() {
+            return f.write_str(text);
+        }
+        let description = match self {
+            Self::PpNumber => "number",
+            Self::CharConst => "character constant",
+            Self::StringLiteral => "string literal",
+            Self::Ident => "identifier",
+            Self::HeaderName => "header name",
+            Self::Whitespace => "whitespace",
+            Self::Newline => "newline",
+            Self::Comment => "comment",
+            Self::Eof => "end of file",
+            _ => "unknown token",
+        };
+        f.write_str(description)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::{Token, TokenKind};
+    use crate::Lexer;
     use slopcc_common::source::FileId;
     use slopcc_common::span::Span;
 
@@ -85,6 +207,12 @@ mod tests {
         FileId::new_for_tests(0)
     }
 
+    #[test]
+    fn interned_symbol_adds_four_bytes() {
+        // 12-byte span, kind, two flags, then the niche-packed `Option<Symbol>`.
+        assert_eq!(std::mem::size_of::<Token>(), 20);
+    }
+
     #[test]
     fn token_construction() {
         let token = Token::new(TokenKind::Ident, Span::new(fid(), 2, 5));
@@ -92,6 +220,96 @@ mod tests {
         assert_eq!(token.span, Span::new(fid(), 2, 5));
     }
 
+    #[test]
+    fn category_predicates() {
+        assert!(TokenKind::Semi.is_punctuator());
+        assert!(TokenKind::HashHash.is_punctuator());
+        assert!(!TokenKind::Ident.is_punctuator());
+
+        assert!(TokenKind::PpNumber.is_literal());
+        assert!(TokenKind::CharConst.is_literal());
+        assert!(TokenKind::StringLiteral.is_literal());
+        assert!(!TokenKind::HeaderName.is_literal());
+
+        assert!(TokenKind::Whitespace.is_trivia());
+        assert!(TokenKind::Comment.is_trivia());
+        assert!(TokenKind::Newline.is_trivia());
+        assert!(!TokenKind::Eof.is_trivia());
+
+        assert!(TokenKind::Eof.is_eof());
+        assert!(!TokenKind::Unknown.is_eof());
+    }
+
+    const PUNCTUATORS: [TokenKind; 48] = [
+        TokenKind::Hash,
+        TokenKind::HashHash,
+        TokenKind::LParen,
+        TokenKind::RParen,
+        TokenKind::LBracket,
+        TokenKind::RBracket,
+        TokenKind::LBrace,
+        TokenKind::RBrace,
+        TokenKind::Comma,
+        TokenKind::Semi,
+        TokenKind::Colon,
+        TokenKind::Ellipsis,
+        TokenKind::Dot,
+        TokenKind::Arrow,
+        TokenKind::Plus,
+        TokenKind::Minus,
+        TokenKind::Star,
+        TokenKind::Slash,
+        TokenKind::Percent,
+        TokenKind::PlusPlus,
+        TokenKind::MinusMinus,
+        TokenKind::Eq,
+        TokenKind::Ne,
+        TokenKind::Lt,
+        TokenKind::Gt,
+        TokenKind::Le,
+        TokenKind::Ge,
+        TokenKind::And,
+        TokenKind::Or,
+        TokenKind::Not,
+        TokenKind::Amp,
+        TokenKind::Pipe,
+        TokenKind::Caret,
+        TokenKind::Tilde,
+        TokenKind::Shl,
+        TokenKind::Shr,
+        TokenKind::Assign,
+        TokenKind::PlusAssign,
+        TokenKind::MinusAssign,
+        TokenKind::StarAssign,
+        TokenKind::SlashAssign,
+        TokenKind::PercentAssign,
+        TokenKind::AmpAssign,
+        TokenKind::PipeAssign,
+        TokenKind::CaretAssign,
+        TokenKind::ShlAssign,
+        TokenKind::ShrAssign,
+        TokenKind::Question,
+    ];
+
+    #[test]
+    fn punctuator_display_round_trips_through_lexer() {
+        for kind in PUNCTUATORS {
+            assert!(kind.is_punctuator());
+            let text = kind.to_string();
+            let tokens = Lexer::tokenize(text.as_bytes(), fid());
+            assert_eq!(tokens.len(), 2, "`{text}` should lex as one token");
+            assert_eq!(tokens[0].kind, kind, "`{text}` lexed as the wrong kind");
+        }
+    }
+
+    #[test]
+    fn content_kinds_display_descriptions() {
+        assert_eq!(TokenKind::Ident.to_string(), "identifier");
+        assert_eq!(TokenKind::StringLiteral.to_string(), "string literal");
+        assert_eq!(TokenKind::Eof.to_string(), "end of file");
+        assert_eq!(TokenKind::Ident.spelling(), None);
+    }
+
     #[test]
     fn token_is_copy() {
         let token = Token::new(TokenKind::PpNumber, Span::new(fid(), 0, 1));
@@ -1,13 +1,32 @@
 use std::{
   ffi::OsString,
-  path::PathBuf,
+  path::{
+    Path,
+    PathBuf,
+  },
 };
 
 use clap::{
+  error::{
+    ContextKind,
+    ContextValue,
+    ErrorKind,
+  },
   ArgAction,
+  ArgMatches,
+  CommandFactory,
+  FromArgMatches,
   Parser,
 };
 
+use crate::{
+  deps,
+  warnings::{
+    self,
+    WarningConfig,
+  },
+};
+
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub enum CompileMode {
   PreprocessOnly,
@@ -16,19 +35,259 @@ pub enum CompileMode {
   Link,
 }
 
+/// Which headers `-M`-style dependency output lists.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum DepMode {
+  /// `-M`: every header, system headers included.
+  AllHeaders,
+  /// `-MM`: headers outside the system include directories only.
+  UserHeaders,
+}
+
+/// Input language named by `-x` or implied by a file extension.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Language {
+  C,
+  CHeader,
+  /// Already-preprocessed C (`.i`).
+  CppOutput,
+}
+
+impl Language {
+  /// Parses a `-x` language name. `none` is handled by the caller.
+  #[must_use]
+  pub fn from_name(name: &str) -> Option<Self> {
+    match name {
+      "c" => Some(Self::C),
+      "c-header" => Some(Self::CHeader),
+      "cpp-output" => Some(Self::CppOutput),
+      _ => None,
+    }
+  }
+
+  /// Language implied by `path`'s extension, as used when no `-x` applies.
+  #[must_use]
+  pub fn from_path(path: &Path) -> Option<Self> {
+    match path.extension()?.to_str()? {
+      "c" => Some(Self::C),
+      "h" => Some(Self::CHeader),
+      "i" => Some(Self::CppOutput),
+      _ => None,
+    }
+  }
+}
+
+/// `--color` setting for diagnostics.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, clap::ValueEnum)]
+pub enum ColorChoice {
+  /// Color only when stderr is a terminal.
+  #[default]
+  Auto,
+  Always,
+  Never,
+}
+
+/// Language standard selected by `-std=`; `C17` when unspecified.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub enum StdVersion {
+  C89,
+  C99,
+  C11,
+  #[default]
+  C17,
+  C23,
+  Gnu89,
+  Gnu99,
+  Gnu11,
+  Gnu17,
+  Gnu23,
+}
+
+impl StdVersion {
+  /// Parses a `-std=` value, including GCC's aliases (`c90`, `c18`, `c2x`, ...).
+  #[must_use]
+  pub fn from_name(name: &str) -> Option<Self> {
+    match name {
+      "c89" | "c90" | "iso9899:1990" => Some(Self::C89),
+      "c99" | "c9x" | "iso9899:1999" => Some(Self::C99),
+      "c11" | "c1x" | "iso9899:2011" => Some(Self::C11),
+      "c17" | "c18" | "iso9899:2017" | "iso9899:2018" => Some(Self::C17),
+      "c23" | "c2x" | "iso9899:2024" => Some(Self::C23),
+      "gnu89" | "gnu90" => Some(Self::Gnu89),
+      "gnu99" | "gnu9x" => Some(Self::Gnu99),
+      "gnu11" | "gnu1x" => Some(Self::Gnu11),
+      "gnu17" | "gnu18" => Some(Self::Gnu17),
+      "gnu23" | "gnu2x" => Some(Self::Gnu23),
+      _ => None,
+    }
+  }
+
+  /// Whether this is a `gnu*` dialect rather than strict ISO C.
+  #[must_use]
+  pub fn is_gnu(self) -> bool {
+    matches!(self, Self::Gnu89 | Self::Gnu99 | Self::Gnu11 | Self::Gnu17 | Self::Gnu23)
+  }
+}
+
+/// Optimization level from `-O`; a bare `-O` means `-O1`.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum OptLevel {
+  O0,
+  O1,
+  O2,
+  O3,
+  /// `-Os`: optimize for size.
+  Os,
+  /// `-Oz`: optimize aggressively for size.
+  Oz,
+  /// `-Og`: optimize for debugging.
+  Og,
+}
+
+impl OptLevel {
+  /// Parses the text after `-O`.
+  #[must_use]
+  pub fn from_flag(level: &str) -> Option<Self> {
+    match level {
+      "" | "1" => Some(Self::O1),
+      "0" => Some(Self::O0),
+      "2" => Some(Self::O2),
+      "3" => Some(Self::O3),
+      "s" => Some(Self::Os),
+      "z" => Some(Self::Oz),
+      "g" => Some(Self::Og),
+      _ => None,
+    }
+  }
+}
+
+/// Debug-info level from the text after `-g`: a bare `-g` means level 2,
+/// `-g0`..`-g3` set it directly, and the format spellings `-ggdb[N]` and
+/// `-gdwarf[-V]` count as level 2 unless `-ggdb` carries a level.
+fn debug_level(flag: &str) -> Option<u8> {
+  let level = |digits: &str| match digits {
+    "" => Some(2),
+    "0" | "1" | "2" | "3" => digits.parse().ok(),
+    _ => None,
+  };
+  if let Some(digits) = flag.strip_prefix("gdb") {
+    return level(digits);
+  }
+  if let Some(version) = flag.strip_prefix("dwarf") {
+    let valid = version.is_empty()
+      || version
+        .strip_prefix('-')
+        .is_some_and(|v| !v.is_empty() && v.bytes().all(|b| b.is_ascii_digit()));
+    return valid.then_some(2);
+  }
+  level(flag)
+}
+
+/// How strictly ISO C is enforced: `-pedantic` reports the `pedantic`
+/// warnings and `-pedantic-errors` makes them errors.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub enum Pedantic {
+  #[default]
+  Off,
+  Warn,
+  Errors,
+}
+
+/// A `-D` or `-U` from the command line.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct MacroDef {
+  /// Everything before the first `=`, including any parameter list.
+  pub name: String,
+  /// Replacement text: `1` for `-DNAME`, empty for `-DNAME=`. `None` means
+  /// `-UNAME`.
+  pub value: Option<String>,
+}
+
+impl MacroDef {
+  /// Parses a `-D` argument (`NAME`, `NAME=`, `NAME=VALUE`).
+  #[must_use]
+  pub fn define(arg: &str) -> Self {
+    let (name, value) = arg.split_once('=').unwrap_or((arg, "1"));
+    Self {
+      name: name.to_owned(),
+      value: Some(value.to_owned()),
+    }
+  }
+
+  #[must_use]
+  pub fn undefine(name: &str) -> Self {
+    Self {
+      name: name.to_owned(),
+      value: None,
+    }
+  }
+}
+
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub struct CliOptions {
   pub inputs: Vec<PathBuf>,
+  /// Language forced by the last `-x` before each input, parallel to
+  /// `inputs`; `None` means detect from the extension.
+  pub languages: Vec<Option<Language>>,
   pub output: Option<PathBuf>,
   pub mode: CompileMode,
+  /// `-fsyntax-only`: report diagnostics and write no output.
+  pub syntax_only: bool,
+  /// `-save-temps`: keep intermediate `.i`/`.s` files in the current
+  /// directory.
+  pub save_temps: bool,
+  /// `-dM`: with `-E`, print the macros in effect instead of the sources.
+  pub dump_macros: bool,
+  pub deps: Option<DepMode>,
+  /// `-MD`/`-MMD`: write dependencies to a `.d` file alongside the normal
+  /// output instead of replacing it.
+  pub dep_output: Option<DepMode>,
+  pub dep_file: Option<PathBuf>,
+  /// Rule targets from `-MT`/`-MQ` in command-line order; empty means the
+  /// derived `.o` name.
+  pub dep_targets: Vec<String>,
   pub include_dirs: Vec<PathBuf>,
-  pub defines: Vec<OsString>,
-  pub undefs: Vec<OsString>,
-  pub std: Option<OsString>,
-  pub opt: Option<OsString>,
+  /// `-iquote` dirs, searched before `include_dirs` for quoted includes
+  /// only.
+  pub quote_include_dirs: Vec<PathBuf>,
+  /// `-isystem` dirs, searched after `include_dirs` and treated as system
+  /// headers.
+  pub system_include_dirs: Vec<PathBuf>,
+  /// `-nostdinc`: skip the built-in system include dirs.
+  pub nostdinc: bool,
+  /// `-include` files, read ahead of every input in order.
+  pub forced_includes: Vec<PathBuf>,
+  /// `-D` and `-U` in command-line order; later entries win.
+  pub macros: Vec<MacroDef>,
+  /// `-l` library names, kept for the link step.
+  pub libs: Vec<String>,
+  /// `-L` library search dirs, kept for the link step.
+  pub lib_dirs: Vec<PathBuf>,
+  pub preserve_comments: bool,
+  /// `# <line> "file"` markers in preprocessed output; `-P` turns them off.
+  pub line_markers: bool,
+  pub warnings: WarningConfig,
+  pub color: ColorChoice,
+  pub std: StdVersion,
+  pub opt: Option<OptLevel>,
+  /// `-g` level, 0 (no debug info) to 3; the last `-g` wins. Parsed so
+  /// build systems can pass it, though no debug info is emitted yet.
+  pub debug_level: u8,
+  pub pedantic: Pedantic,
   pub verbose: bool,
   pub dry_run: bool,
+  /// Unrecognized `-W` options. Like GCC, they only draw a warning.
+  pub ignored_options: Vec<String>,
+  /// Flags from [`NOOP_FLAGS`] and [`NOOP_PREFIXES`], accepted so slopcc can
+  /// stand in as `CC` but not acted on. Listed under `-v`.
+  pub noop_options: Vec<String>,
   pub show_version: bool,
+  pub show_help: bool,
+  /// `-dumpmachine`: print the target triple and exit.
+  pub dump_machine: bool,
+  /// `-print-file-name=<name>`: print where the link step would find `name`
+  /// and exit.
+  pub print_file_name: Option<String>,
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -37,6 +296,24 @@ pub enum CliError {
   Clap(#[from] clap::Error),
   #[error("no input files")]
   NoInputFiles,
+  #[error("cannot specify -o with -c or -S with multiple files")]
+  OutputWithMultipleInputs,
+  #[error("cannot specify -o more than once ('{}' and '{}')", .0.display(), .1.display())]
+  RepeatedOutput(PathBuf, PathBuf),
+  #[error("language {0} not recognized")]
+  UnknownLanguage(String),
+  #[error("unrecognized language standard '-std={0}'")]
+  UnknownStd(String),
+  #[error("invalid optimization level '-O{0}'")]
+  UnknownOptLevel(String),
+  #[error("unrecognized debug output level '{0}'")]
+  UnknownDebugLevel(String),
+  #[error("-MF requires a dependency mode such as -M, -MM, -MD or -MMD")]
+  DepFileWithoutDeps,
+  /// An option slopcc does not know. Fatal, except for `-W` options, which
+  /// land in [`CliOptions::ignored_options`] instead.
+  #[error("unrecognized command-line option '{0}'")]
+  UnknownOption(String),
 }
 
 #[derive(Parser, Debug)]
@@ -53,24 +330,86 @@ struct ClapCli {
   compile_only: bool,
   #[arg(short = 'c', action = ArgAction::SetTrue)]
   assemble_only: bool,
-  #[arg(short = 'o')]
-  output: Option<PathBuf>,
-  #[arg(short = 'I')]
+  #[arg(short = 'o', allow_hyphen_values = true)]
+  output: Vec<PathBuf>,
+  #[arg(long = "fsyntax-only", action = ArgAction::SetTrue)]
+  syntax_only: bool,
+  #[arg(long = "save-temps", action = ArgAction::SetTrue)]
+  save_temps: bool,
+  #[arg(long = "dM", action = ArgAction::SetTrue)]
+  dump_macros: bool,
+  #[arg(short = 'x', allow_hyphen_values = true)]
+  languages: Vec<String>,
+  #[arg(long = "M", action = ArgAction::SetTrue)]
+  deps_all: bool,
+  #[arg(long = "MM", action = ArgAction::SetTrue)]
+  deps_user: bool,
+  #[arg(long = "MD", action = ArgAction::SetTrue)]
+  dep_output_all: bool,
+  #[arg(long = "MMD", action = ArgAction::SetTrue)]
+  dep_output_user: bool,
+  #[arg(long = "MF", allow_hyphen_values = true)]
+  dep_file: Option<PathBuf>,
+  #[arg(long = "MT", allow_hyphen_values = true)]
+  dep_targets: Vec<String>,
+  #[arg(long = "MQ", allow_hyphen_values = true)]
+  dep_quoted_targets: Vec<String>,
+  #[arg(short = 'I', allow_hyphen_values = true)]
   include_dirs: Vec<PathBuf>,
-  #[arg(short = 'D')]
-  defines: Vec<OsString>,
-  #[arg(short = 'U')]
-  undefs: Vec<OsString>,
+  #[arg(long = "iquote", allow_hyphen_values = true)]
+  quote_include_dirs: Vec<PathBuf>,
+  #[arg(long = "isystem", allow_hyphen_values = true)]
+  system_include_dirs: Vec<PathBuf>,
+  #[arg(long = "nostdinc", action = ArgAction::SetTrue)]
+  nostdinc: bool,
+  #[arg(long = "include", allow_hyphen_values = true)]
+  forced_includes: Vec<PathBuf>,
+  #[arg(short = 'D', allow_hyphen_values = true)]
+  defines: Vec<String>,
+  #[arg(short = 'U', allow_hyphen_values = true)]
+  undefs: Vec<String>,
+  #[arg(short = 'l', allow_hyphen_values = true)]
+  libs: Vec<String>,
+  #[arg(short = 'L', allow_hyphen_values = true)]
+  lib_dirs: Vec<PathBuf>,
+  #[arg(short = 'C', action = ArgAction::SetTrue)]
+  preserve_comments: bool,
+  #[arg(short = 'P', action = ArgAction::SetTrue)]
+  no_line_markers: bool,
+  #[arg(short = 'W')]
+  warning_flags: Vec<String>,
+  #[arg(short = 'w', action = ArgAction::SetTrue)]
+  suppress_warnings: bool,
+  #[arg(long = "pedantic", action = ArgAction::SetTrue)]
+  pedantic: bool,
+  #[arg(long = "pedantic-errors", action = ArgAction::SetTrue)]
+  pedantic_errors: bool,
+  #[arg(
+    long = "color",
+    value_enum,
+    num_args = 0..=1,
+    require_equals = true,
+    default_missing_value = "always"
+  )]
+  color: Option<ColorChoice>,
   #[arg(long = "std")]
-  std: Option<OsString>,
+  std: Option<String>,
   #[arg(short = 'O')]
-  opt: Option<OsString>,
+  opt: Vec<String>,
+  #[arg(short = 'g')]
+  debug: Vec<String>,
   #[arg(short = 'v', action = ArgAction::SetTrue)]
   verbose: bool,
   #[arg(short = '#', action = ArgAction::Count)]
   dry_run_count: u8,
   #[arg(long = "version", action = ArgAction::SetTrue)]
   show_version: bool,
+  #[arg(short = 'h', long = "help", action = ArgAction::SetTrue)]
+  show_help: bool,
+  #[arg(long = "dumpmachine", action = ArgAction::SetTrue)]
+  dump_machine: bool,
+  #[arg(long = "print-file-name")]
+  print_file_name: Option<String>,
   #[arg(value_name = "INPUT")]
   inputs: Vec<PathBuf>,
 }
@@ -79,14 +418,45 @@ pub fn parse_args<I>(args: I) -> Result<CliOptions, CliError>
 where
   I: IntoIterator<Item = OsString>,
 {
-  let normalized = normalize_gcc_args(args.into_iter().collect());
-  let parsed = ClapCli::try_parse_from(normalized)?;
+  let (noop_options, args) = split_noop_flags(args);
+  let matches = ClapCli::command()
+    .try_get_matches_from(normalize_gcc_args(args.clone()))
+    .map_err(|error| unknown_option(error, &args))?;
+  let dep_targets = dependency_targets(&matches);
+  let languages = input_languages(&matches)?;
+  let macros = macro_defs(&matches);
+  let parsed = ClapCli::from_arg_matches(&matches)?;
 
-  if !parsed.show_version && parsed.inputs.is_empty() {
+  let query = parsed.show_version
+    || parsed.show_help
+    || parsed.dump_machine
+    || parsed.print_file_name.is_some();
+  if !query && parsed.inputs.is_empty() {
     return Err(CliError::NoInputFiles);
   }
 
-  let mode = if parsed.preprocess_only {
+  let deps = if parsed.deps_user {
+    Some(DepMode::UserHeaders)
+  } else if parsed.deps_all {
+    Some(DepMode::AllHeaders)
+  } else {
+    None
+  };
+
+  let dep_output = if parsed.dep_output_user {
+    Some(DepMode::UserHeaders)
+  } else if parsed.dep_output_all {
+    Some(DepMode::AllHeaders)
+  } else {
+    None
+  };
+
+  if parsed.dep_file.is_some() && deps.is_none() && dep_output.is_none() {
+    return Err(CliError::DepFileWithoutDeps);
+  }
+
+  // -M and -MM imply -E, as in GCC; -MD and -MMD do not.
+  let mode = if parsed.preprocess_only || deps.is_some() {
     CompileMode::PreprocessOnly
   } else if parsed.compile_only {
     CompileMode::CompileOnly
@@ -96,49 +466,430 @@ where
     CompileMode::Link
   };
 
+  // clap would reject the repeat itself; name both paths instead.
+  let mut outputs = parsed.output.into_iter();
+  let output = outputs.next();
+  if let (Some(first), Some(second)) = (&output, outputs.next()) {
+    return Err(CliError::RepeatedOutput(first.clone(), second));
+  }
+
+  // One -o cannot hold several objects or assembly files; linking can.
+  if output.is_some()
+    && parsed.inputs.len() > 1
+    && matches!(mode, CompileMode::CompileOnly | CompileMode::AssembleOnly)
+  {
+    return Err(CliError::OutputWithMultipleInputs);
+  }
+
+  let std = match parsed.std {
+    Some(name) => StdVersion::from_name(&name).ok_or(CliError::UnknownStd(name))?,
+    None => StdVersion::default(),
+  };
+
+  // The last -O wins, as in GCC.
+  let opt = parsed
+    .opt
+    .last()
+    .map(|level| OptLevel::from_flag(level).ok_or_else(|| CliError::UnknownOptLevel(level.clone())))
+    .transpose()?;
+
+  let debug_level = match parsed.debug.last() {
+    Some(flag) => debug_level(flag).ok_or_else(|| CliError::UnknownDebugLevel(flag.clone()))?,
+    None => 0,
+  };
+
+  let pedantic = if parsed.pedantic_errors {
+    Pedantic::Errors
+  } else if parsed.pedantic {
+    Pedantic::Warn
+  } else {
+    Pedantic::Off
+  };
+
+  let mut warnings = WarningConfig::default();
+  warnings.suppress_all = parsed.suppress_warnings;
+  // Applied first so an explicit -Wno-pedantic still wins.
+  match pedantic {
+    Pedantic::Off => {}
+    Pedantic::Warn => warnings.apply_flag("pedantic"),
+    Pedantic::Errors => warnings.apply_flag("error=pedantic"),
+  }
+  let mut ignored_options = Vec::new();
+  for flag in &parsed.warning_flags {
+    if warnings::is_known_flag(flag) {
+      warnings.apply_flag(flag);
+    } else {
+      ignored_options.push(format!("-W{flag}"));
+    }
+  }
+
   Ok(CliOptions {
     inputs: parsed.inputs,
-    output: parsed.output,
+    languages,
+    output,
     mode,
+    syntax_only: parsed.syntax_only,
+    save_temps: parsed.save_temps,
+    dump_macros: parsed.dump_macros,
+    deps,
+    dep_output,
+    dep_file: parsed.dep_file,
+    dep_targets,
     include_dirs: parsed.include_dirs,
-    defines: parsed.defines,
-    undefs: parsed.undefs,
-    std: parsed.std,
-    opt: parsed.opt,
+    quote_include_dirs: parsed.quote_include_dirs,
+    system_include_dirs: parsed.system_include_dirs,
+    nostdinc: parsed.nostdinc,
+    forced_includes: parsed.forced_includes,
+    macros,
+    libs: parsed.libs,
+    lib_dirs: parsed.lib_dirs,
+    preserve_comments: parsed.preserve_comments,
+    line_markers: !parsed.no_line_markers,
+    warnings,
+    color: parsed.color.unwrap_or_default(),
+    std,
+    opt,
+    debug_level,
+    pedantic,
     verbose: parsed.verbose,
     dry_run: parsed.dry_run_count > 0,
+    ignored_options,
+    noop_options,
     show_version: parsed.show_version,
+    show_help: parsed.show_help,
+    dump_machine: parsed.dump_machine,
+    print_file_name: parsed.print_file_name,
   })
 }
 
+/// `--help` text, kept by hand so it lists exactly the flags parsed above.
+pub const USAGE: &str = "\
+Usage: slopcc [options] file...
+Options:
+  -E                       Preprocess only; write to stdout or -o
+  -S                       Compile only; do not assemble or link
+  -c                       Compile and assemble, but do not link
+  -o <file>                Place the output into <file> (- for stdout)
+  -fsyntax-only            Check the input for errors; write no output
+  -save-temps              Keep intermediate files in the current directory
+  -dM                      With -E, print macro definitions instead
+  -x <language>            Treat later inputs as c, c-header or cpp-output
+                           (none restores extension-based detection)
+  -I <dir>                 Add <dir> to the include search path
+  -iquote <dir>            Add <dir> to the search path for quoted includes
+  -isystem <dir>           Add <dir> to the system include search path
+  -nostdinc                Do not search the system include directories
+  -include <file>          Process <file> before each input
+  -D <macro>[=<value>]     Define <macro>
+  -U <macro>               Undefine <macro>
+  -l <library>             Link with <library>
+  -L <dir>                 Add <dir> to the library search path
+  -C                       Keep comments in preprocessed output
+  -P                       Omit line markers from preprocessed output
+  -M, -MM                  Output make rules for all / non-system headers
+  -MD, -MMD                Like -M/-MM, written to a .d file while compiling
+  -MF <file>               Write dependency rules to <file>
+  -MT <target>             Set the rule target
+  -MQ <target>             Set the rule target, quoted for make
+  -w                       Suppress all warnings
+  --color[=<when>]         Color diagnostics: auto, always or never
+  -W<warning>              Enable a warning (-Wall, -Wextra, -Wno-<warning>)
+  -Werror[=<warning>]      Make warnings into errors
+  -pedantic                Warn about extensions to ISO C
+  -pedantic-errors         Like -pedantic, but as errors
+  -std=<standard>          Assume the input follows <standard>
+  -O<level>                Set the optimization level (0-3, s, z, g)
+  -pipe, -pthread, -fPIC   Accepted for cc compatibility and ignored, like
+                           -march=, -mtune=, -fuse-ld=, -Wl, and -Wa,
+  -g[<level>]              Request debug info (0-3, gdb, dwarf); accepted
+                           but not emitted yet
+  -v                       Show the compilation steps
+  -###                     Print the steps without running them
+  --version                Print the compiler version
+  -dumpmachine             Print the target triple
+  -print-file-name=<name>  Print the path the linker would use for <name>
+  -h, --help               Display this information
+";
+
+/// Merges `-MT` and `-MQ` values back into command-line order, quoting the
+/// `-MQ` ones for make.
+fn dependency_targets(matches: &ArgMatches) -> Vec<String> {
+  let plain = indexed_values(matches, "dep_targets").map(|(index, target)| (index, target.clone()));
+  let quoted = indexed_values(matches, "dep_quoted_targets")
+    .map(|(index, target)| (index, deps::quote_target(target)));
+  let mut targets: Vec<_> = plain.chain(quoted).collect();
+  targets.sort_by_key(|&(index, _)| index);
+  targets.into_iter().map(|(_, target)| target).collect()
+}
+
+/// Merges `-D` and `-U` back into command-line order.
+fn macro_defs(matches: &ArgMatches) -> Vec<MacroDef> {
+  let defines =
+    indexed_values(matches, "defines").map(|(index, arg)| (index, MacroDef::define(arg)));
+  let undefs =
+    indexed_values(matches, "undefs").map(|(index, name)| (index, MacroDef::undefine(name)));
+  let mut macros: Vec<_> = defines.chain(undefs).collect();
+  macros.sort_by_key(|&(index, _)| index);
+  macros.into_iter().map(|(_, def)| def).collect()
+}
+
+/// Applies each `-x` to the inputs that follow it, as GCC does; `-x none`
+/// returns to extension-based detection.
+fn input_languages(matches: &ArgMatches) -> Result<Vec<Option<Language>>, CliError> {
+  let mut selections = Vec::new();
+  for (index, name) in indexed_values(matches, "languages") {
+    let language = match name.as_str() {
+      "none" => None,
+      name => {
+        Some(Language::from_name(name).ok_or_else(|| CliError::UnknownLanguage(name.to_owned()))?)
+      }
+    };
+    selections.push((index, language));
+  }
+
+  let inputs = matches.indices_of("inputs").into_iter().flatten();
+  Ok(
+    inputs
+      .map(|input| {
+        selections
+          .iter()
+          .rev()
+          .find(|&&(index, _)| index < input)
+          .and_then(|&(_, language)| language)
+      })
+      .collect(),
+  )
+}
+
+fn indexed_values<'a>(
+  matches: &'a ArgMatches,
+  id: &str,
+) -> impl Iterator<Item = (usize, &'a String)> {
+  let indices = matches.indices_of(id).into_iter().flatten();
+  let values = matches.get_many::<String>(id).into_iter().flatten();
+  indices.zip(values)
+}
+
+/// GCC options spelled with a single dash that clap parses as `--name`.
+/// `-name` and `-name=value` are both rewritten.
+const SINGLE_DASH_LONG: &[&str] = &[
+  "std",
+  "fsyntax-only",
+  "save-temps",
+  "dM",
+  "iquote",
+  "isystem",
+  "nostdinc",
+  "pedantic-errors",
+  "pedantic",
+  "dumpmachine",
+  "print-file-name",
+  "include",
+  "MMD",
+  "MM",
+  "MD",
+  "MF",
+  "MT",
+  "MQ",
+  "M",
+];
+
+/// Single-dash long options that also accept a value glued to the name
+/// (`-MFdeps.d`), rewritten to `--name=value`.
+const ATTACHED_VALUE_LONG: &[&str] = &["MF", "MT", "MQ"];
+
+/// Flags that change code generation, linking or how the driver runs its
+/// tools. None of those exist yet, so the flags are accepted and dropped.
+pub const NOOP_FLAGS: &[&str] = &[
+  "-pipe",
+  "-pthread",
+  "-fPIC",
+  "-fpic",
+  "-fPIE",
+  "-fpie",
+  "-fno-common",
+  "-fno-strict-aliasing",
+  "-fno-omit-frame-pointer",
+  "-ffunction-sections",
+  "-fdata-sections",
+  "-fno-stack-protector",
+  "-fstack-protector-strong",
+  "-rdynamic",
+];
+
+/// Prefixes of ignored flags that carry a value (`-march=native`,
+/// `-Wl,--as-needed`, `-fuse-ld=lld` until there is a link step).
+pub const NOOP_PREFIXES: &[&str] =
+  &["-march=", "-mtune=", "-fvisibility=", "-fuse-ld=", "-Wl,", "-Wa,"];
+
+/// Splits the [`NOOP_FLAGS`] and [`NOOP_PREFIXES`] options out of `args`,
+/// returning them apart from the arguments left for clap.
+fn split_noop_flags<I>(args: I) -> (Vec<String>, Vec<OsString>)
+where
+  I: IntoIterator<Item = OsString>,
+{
+  let mut noops = Vec::new();
+  let mut rest = Vec::new();
+  let mut value_next = false;
+  for arg in args {
+    if std::mem::take(&mut value_next) {
+      rest.push(arg);
+      continue;
+    }
+    value_next = takes_separate_value(&arg);
+    match arg.to_str() {
+      Some(flag)
+        if NOOP_FLAGS.contains(&flag)
+          || NOOP_PREFIXES.iter().any(|prefix| flag.starts_with(prefix)) =>
+      {
+        noops.push(flag.to_owned());
+      }
+      _ => rest.push(arg),
+    }
+  }
+  (noops, rest)
+}
+
+/// Turns clap's unknown-argument error into [`CliError::UnknownOption`].
+///
+/// Clap names only the letter it choked on (`-s` for `-shared`), and only
+/// after normalization, so the option reported is the `args` element whose
+/// addition first makes the command line fail this way.
+fn unknown_option(error: clap::Error, args: &[OsString]) -> CliError {
+  if error.kind() != ErrorKind::UnknownArgument {
+    return CliError::Clap(error);
+  }
+  let unknown = (1..args.len()).find(|&end| {
+    ClapCli::command()
+      .try_get_matches_from(normalize_gcc_args(args[..=end].to_vec()))
+      .is_err_and(|error| error.kind() == ErrorKind::UnknownArgument)
+  });
+  match unknown {
+    Some(index) => CliError::UnknownOption(args[index].to_string_lossy().into_owned()),
+    None => match error.get(ContextKind::InvalidArg) {
+      Some(ContextValue::String(arg)) => CliError::UnknownOption(arg.clone()),
+      _ => CliError::Clap(error),
+    },
+  }
+}
+
+/// Options whose value may be the next argument (`-o out`, `-MF deps.d`).
+/// That argument is a value even when it starts with `-` (`-o -mout.i`).
+const SEPARATE_VALUE_OPTIONS: &[&str] = &[
+  "-o", "-x", "-I", "-D", "-U", "-l", "-L", "-MF", "-MT", "-MQ", "-include", "-iquote", "-isystem",
+];
+
+/// Whether the argument after `arg` is its value, including for a cluster
+/// that ends in a bare value flag (`-co out`).
+fn takes_separate_value(arg: &OsString) -> bool {
+  let Some(arg) = arg.to_str() else {
+    return false;
+  };
+  SEPARATE_VALUE_OPTIONS.contains(&arg)
+    || split_short_cluster(arg)
+      .and_then(|split| split.last()?.to_str().map(|last| SEPARATE_VALUE_OPTIONS.contains(&last)))
+      .unwrap_or(false)
+}
+
 fn normalize_gcc_args(args: Vec<OsString>) -> Vec<OsString> {
   let mut normalized = Vec::with_capacity(args.len());
+  let mut value_next = false;
   for arg in args {
-    if let Some(s) = arg.to_str() {
-      if let Some(rest) = s.strip_prefix("-std=") {
-        let mut mapped = OsString::from("--std=");
-        mapped.push(rest);
-        normalized.push(mapped);
-        continue;
-      }
-      if s == "-std" {
-        normalized.push(OsString::from("--std"));
-        continue;
-      }
+    if std::mem::take(&mut value_next) {
+      normalized.push(arg);
+      continue;
+    }
+    value_next = takes_separate_value(&arg);
+    // A bare -O or -g takes no value; spell out its meaning for clap.
+    if arg == "-O" {
+      normalized.push(OsString::from("-O1"));
+      continue;
+    }
+    if arg == "-g" {
+      normalized.push(OsString::from("-g2"));
+      continue;
+    }
+    if let Some(mapped) = arg.to_str().and_then(map_single_dash_long) {
+      normalized.push(mapped);
+      continue;
+    }
+    if let Some(split) = arg.to_str().and_then(split_short_cluster) {
+      normalized.extend(split);
+      continue;
     }
     normalized.push(arg);
   }
   normalized
 }
 
+/// Short flags that take no value and may be clustered (`-cv`).
+const BOOLEAN_SHORTS: &[char] = &['E', 'S', 'c', 'C', 'P', 'v', 'w', 'h'];
+
+/// Short flags whose value may be glued on (`-O2`, `-Iinc`).
+const VALUE_SHORTS: &[char] = &['o', 'x', 'I', 'D', 'U', 'l', 'L', 'W', 'O'];
+
+/// Splits a cluster that starts with a boolean short flag (`-cv` becomes
+/// `-c -v`). The first value-taking flag keeps the rest as its value
+/// (`-co out` becomes `-c -o out`). Clusters with unknown letters, and args
+/// that start with a value-taking flag, are left alone.
+fn split_short_cluster(arg: &str) -> Option<Vec<OsString>> {
+  let body = arg.strip_prefix('-')?;
+  if body.len() < 2 || !body.starts_with(BOOLEAN_SHORTS) {
+    return None;
+  }
+  let mut split = Vec::new();
+  for (offset, flag) in body.char_indices() {
+    if VALUE_SHORTS.contains(&flag) {
+      split.push(OsString::from(format!("-{}", &body[offset..])));
+      return Some(split);
+    }
+    if !BOOLEAN_SHORTS.contains(&flag) {
+      return None;
+    }
+    split.push(OsString::from(format!("-{flag}")));
+  }
+  Some(split)
+}
+
+fn map_single_dash_long(arg: &str) -> Option<OsString> {
+  let body = arg.strip_prefix('-')?;
+  let known = SINGLE_DASH_LONG.iter().any(|name| {
+    body
+      .strip_prefix(name)
+      .is_some_and(|rest| rest.is_empty() || rest.starts_with('='))
+  });
+  if known {
+    return Some(OsString::from(format!("--{body}")));
+  }
+  ATTACHED_VALUE_LONG.iter().find_map(|name| {
+    let value = body.strip_prefix(name)?;
+    Some(OsString::from(format!("--{name}={value}")))
+  })
+}
+
 #[cfg(test)]
 mod tests {
   use super::{
+    normalize_gcc_args,
     parse_args,
     CliError,
+    ColorChoice,
     CompileMode,
+    DepMode,
+    Language,
+    MacroDef,
+    OptLevel,
+    Pedantic,
+    StdVersion,
+    WarningConfig,
+  };
+  use std::{
+    ffi::OsString,
+    path::{
+      Path,
+      PathBuf,
+    },
   };
-  use std::ffi::OsString;
 
   fn args(items: &[&str]) -> Vec<OsString> {
     items.iter().map(OsString::from).collect()
@@ -159,8 +910,40 @@ mod tests {
     ]))
     .expect("parser should accept include/define/undef forms");
     assert_eq!(opts.include_dirs.len(), 2);
-    assert_eq!(opts.defines.len(), 2);
-    assert_eq!(opts.undefs.len(), 2);
+    assert_eq!(opts.macros.len(), 4);
+  }
+
+  #[test]
+  fn parses_macro_definitions_in_order() {
+    let opts = parse_args(args(&[
+      "slopcc", "-DFOO", "-DBAR=42", "-D", "BAZ=", "-UFOO", "-DF(x)=x+1", "a.c",
+    ]))
+    .expect("-D/-U should be accepted");
+    let define = |name: &str, value: &str| MacroDef {
+      name: name.to_owned(),
+      value: Some(value.to_owned()),
+    };
+    assert_eq!(
+      opts.macros,
+      [
+        define("FOO", "1"),
+        define("BAR", "42"),
+        define("BAZ", ""),
+        MacroDef::undefine("FOO"),
+        define("F(x)", "x+1"),
+      ]
+    );
+  }
+
+  #[test]
+  fn collects_libraries_and_library_dirs() {
+    let opts = parse_args(args(&[
+      "slopcc", "a.c", "-lm", "-L/usr/lib", "-l", "pthread", "-L", "lib",
+    ]))
+    .expect("-l/-L should be accepted");
+    assert_eq!(opts.libs, ["m", "pthread"]);
+    assert_eq!(opts.lib_dirs, [PathBuf::from("/usr/lib"), PathBuf::from("lib")]);
+    assert_eq!(opts.inputs, [PathBuf::from("a.c")]);
   }
 
   #[test]
@@ -171,7 +954,7 @@ mod tests {
     .expect("parser should accept std/opt/output/dry-run/verbose");
     assert!(opts.dry_run);
     assert!(opts.verbose);
-    assert!(opts.std.is_some());
+    assert_eq!(opts.std, StdVersion::C11);
     assert!(opts.opt.is_some());
     assert!(opts.output.is_some());
 
@@ -180,6 +963,338 @@ mod tests {
     assert!(version_only.show_version);
   }
 
+  #[test]
+  fn queries_do_not_require_inputs() {
+    let opts = parse_args(args(&["slopcc", "-dumpmachine"])).expect("-dumpmachine should parse");
+    assert!(opts.dump_machine);
+
+    let opts = parse_args(args(&["slopcc", "-print-file-name=libc.a"]))
+      .expect("-print-file-name should parse");
+    assert_eq!(opts.print_file_name.as_deref(), Some("libc.a"));
+  }
+
+  #[test]
+  fn parses_std_versions_with_default() {
+    let opts = parse_args(args(&["slopcc", "-std=c11", "a.c"])).expect("-std=c11 should parse");
+    assert_eq!(opts.std, StdVersion::C11);
+    let opts =
+      parse_args(args(&["slopcc", "--std=gnu17", "a.c"])).expect("--std=gnu17 should parse");
+    assert_eq!(opts.std, StdVersion::Gnu17);
+    let opts = parse_args(args(&["slopcc", "a.c"])).expect("no -std should parse");
+    assert_eq!(opts.std, StdVersion::C17);
+  }
+
+  #[test]
+  fn parses_pedantic_levels() {
+    let level = |flags: &[&str]| {
+      let mut items = vec!["slopcc"];
+      items.extend_from_slice(flags);
+      items.push("a.c");
+      parse_args(args(&items)).expect("pedantic flags should parse").pedantic
+    };
+    assert_eq!(level(&[]), Pedantic::Off);
+    assert_eq!(level(&["-pedantic"]), Pedantic::Warn);
+    assert_eq!(level(&["-pedantic-errors"]), Pedantic::Errors);
+    assert_eq!(level(&["-pedantic", "-pedantic-errors"]), Pedantic::Errors);
+
+    let opts = parse_args(args(&["slopcc", "-pedantic-errors", "a.c"])).expect("should parse");
+    assert!(opts.warnings.is_enabled(Some("pedantic")));
+    assert!(opts.warnings.is_error(Some("pedantic")));
+  }
+
+  #[test]
+  fn rejects_unknown_std() {
+    let err =
+      parse_args(args(&["slopcc", "-std=c1z", "a.c"])).expect_err("c1z is not a C standard");
+    assert!(matches!(err, CliError::UnknownStd(ref name) if name == "c1z"));
+  }
+
+  #[test]
+  fn parses_each_opt_level() {
+    for (flag, level) in [
+      ("-O", OptLevel::O1),
+      ("-O0", OptLevel::O0),
+      ("-O1", OptLevel::O1),
+      ("-O2", OptLevel::O2),
+      ("-O3", OptLevel::O3),
+      ("-Os", OptLevel::Os),
+      ("-Oz", OptLevel::Oz),
+      ("-Og", OptLevel::Og),
+    ] {
+      let opts = parse_args(args(&["slopcc", flag, "a.c"])).expect("valid -O level should parse");
+      assert_eq!(opts.opt, Some(level), "{flag}");
+    }
+
+    let opts =
+      parse_args(args(&["slopcc", "-O3", "-O0", "a.c"])).expect("repeated -O should parse");
+    assert_eq!(opts.opt, Some(OptLevel::O0));
+    assert_eq!(parse_args(args(&["slopcc", "a.c"])).expect("no -O should parse").opt, None);
+  }
+
+  #[test]
+  fn rejects_unknown_opt_levels() {
+    for (flag, level) in [("-O9", "9"), ("-Ofoo", "foo")] {
+      let err = parse_args(args(&["slopcc", flag, "a.c"])).expect_err("bad -O level should fail");
+      assert!(matches!(err, CliError::UnknownOptLevel(ref got) if got == level), "{flag}");
+    }
+  }
+
+  #[test]
+  fn parses_debug_levels() {
+    let level = |flags: &[&str]| {
+      let argv: Vec<&str> = ["slopcc"].iter().chain(flags).chain(&["a.c"]).copied().collect();
+      parse_args(args(&argv)).expect("-g should parse").debug_level
+    };
+    assert_eq!(level(&[]), 0);
+    assert_eq!(level(&["-g"]), 2);
+    assert_eq!(level(&["-g0"]), 0);
+    assert_eq!(level(&["-ggdb"]), 2);
+    assert_eq!(level(&["-g3"]), 3);
+    assert_eq!(level(&["-ggdb1", "-gdwarf-4"]), 2);
+    assert_eq!(level(&["-g", "-g0"]), 0);
+    let err = parse_args(args(&["slopcc", "-g7", "a.c"])).expect_err("-g7 should fail");
+    assert!(matches!(err, CliError::UnknownDebugLevel(ref got) if got == "7"));
+  }
+
+  #[test]
+  fn help_does_not_require_inputs() {
+    for flag in ["--help", "-h"] {
+      let opts = parse_args(args(&["slopcc", flag])).expect("help should not require input files");
+      assert!(opts.show_help);
+    }
+  }
+
+  #[test]
+  fn parses_syntax_only_flag() {
+    let opts =
+      parse_args(args(&["slopcc", "-fsyntax-only", "a.c"])).expect("-fsyntax-only should parse");
+    assert!(opts.syntax_only);
+    assert!(!parse_args(args(&["slopcc", "a.c"])).expect("plain input should parse").syntax_only);
+  }
+
+  #[test]
+  fn parses_save_temps_flag() {
+    let opts = parse_args(args(&["slopcc", "-c", "-save-temps", "a.c"]))
+      .expect("-save-temps should be accepted");
+    assert!(opts.save_temps);
+  }
+
+  #[test]
+  fn parses_dump_macros_flag() {
+    let opts = parse_args(args(&["slopcc", "-E", "-dM", "a.c"])).expect("-dM should be accepted");
+    assert!(opts.dump_macros);
+  }
+
+  #[test]
+  fn parses_preserve_comments_flag() {
+    let opts = parse_args(args(&["slopcc", "-E", "-C", "a.c"])).expect("-C should be accepted");
+    assert!(opts.preserve_comments);
+
+    let opts = parse_args(args(&["slopcc", "-E", "a.c"])).expect("-E alone should be accepted");
+    assert!(!opts.preserve_comments);
+  }
+
+  #[test]
+  fn dash_p_disables_line_markers() {
+    let opts = parse_args(args(&["slopcc", "-E", "-P", "a.c"])).expect("-P should be accepted");
+    assert!(!opts.line_markers);
+
+    let opts = parse_args(args(&["slopcc", "-EP", "a.c"])).expect("-EP should be accepted");
+    assert!(!opts.line_markers);
+
+    let opts = parse_args(args(&["slopcc", "-E", "a.c"])).expect("-E alone should be accepted");
+    assert!(opts.line_markers);
+  }
+
+  #[test]
+  fn parses_warning_flags_in_order() {
+    let opts = parse_args(args(&["slopcc", "-Wall", "-Wno-comment", "-Wfoo", "a.c"]))
+      .expect("-W flags should be accepted");
+    assert!(!opts.warnings.suppress_all);
+    assert!(!opts.warnings.is_enabled(Some("comment")));
+    assert!(opts.warnings.is_enabled(Some("foo")));
+
+    let opts = parse_args(args(&["slopcc", "-w", "-Wall", "a.c"])).expect("-w should be accepted");
+    assert!(opts.warnings.suppress_all);
+    assert!(!opts.warnings.is_enabled(Some("comment")));
+  }
+
+  #[test]
+  fn keeps_isystem_dirs_apart_from_include_dirs() {
+    let opts = parse_args(args(&[
+      "slopcc", "-isystem", "s1", "-Ia", "-isystem", "s2", "-I", "b", "x.c",
+    ]))
+    .expect("-isystem should be accepted");
+    assert_eq!(opts.include_dirs, [PathBuf::from("a"), PathBuf::from("b")]);
+    assert_eq!(opts.system_include_dirs, [PathBuf::from("s1"), PathBuf::from("s2")]);
+  }
+
+  #[test]
+  fn keeps_iquote_dirs_apart_from_include_dirs() {
+    let opts = parse_args(args(&["slopcc", "-iquote", "q1", "-Ia", "-iquote", "q2", "x.c"]))
+      .expect("-iquote should be accepted");
+    assert_eq!(opts.include_dirs, [PathBuf::from("a")]);
+    assert_eq!(opts.quote_include_dirs, [PathBuf::from("q1"), PathBuf::from("q2")]);
+  }
+
+  #[test]
+  fn parses_nostdinc_flag() {
+    let opts = parse_args(args(&["slopcc", "-nostdinc", "-Iinc", "a.c"]))
+      .expect("-nostdinc should be accepted");
+    assert!(opts.nostdinc);
+    assert!(!parse_args(args(&["slopcc", "a.c"])).expect("plain input should parse").nostdinc);
+  }
+
+  #[test]
+  fn collects_forced_includes_in_order() {
+    let opts = parse_args(args(&["slopcc", "-include", "a.h", "-include", "b.h", "x.c"]))
+      .expect("-include should be accepted");
+    assert_eq!(opts.forced_includes, [PathBuf::from("a.h"), PathBuf::from("b.h")]);
+    assert_eq!(opts.inputs, [PathBuf::from("x.c")]);
+  }
+
+  #[test]
+  fn parses_color_choice() {
+    let color = |flags: &[&str]| {
+      let mut items = vec!["slopcc"];
+      items.extend(flags);
+      items.push("a.c");
+      parse_args(args(&items)).expect("--color should parse").color
+    };
+    assert_eq!(color(&[]), ColorChoice::Auto);
+    assert_eq!(color(&["--color"]), ColorChoice::Always);
+    assert_eq!(color(&["--color=never"]), ColorChoice::Never);
+    assert_eq!(color(&["--color=auto"]), ColorChoice::Auto);
+  }
+
+  #[test]
+  fn parses_dependency_modes() {
+    let opts = parse_args(args(&["slopcc", "-M", "a.c"])).expect("-M should be accepted");
+    assert_eq!(opts.deps, Some(DepMode::AllHeaders));
+    assert_eq!(opts.mode, CompileMode::PreprocessOnly);
+
+    let opts = parse_args(args(&["slopcc", "-MM", "a.c"])).expect("-MM should be accepted");
+    assert_eq!(opts.deps, Some(DepMode::UserHeaders));
+
+    let opts = parse_args(args(&["slopcc", "-c", "a.c"])).expect("-c should be accepted");
+    assert_eq!(opts.deps, None);
+  }
+
+  #[test]
+  fn parses_side_effect_dependency_modes() {
+    let opts = parse_args(args(&["slopcc", "-c", "-MD", "a.c"])).expect("-MD should be accepted");
+    assert_eq!(opts.dep_output, Some(DepMode::AllHeaders));
+    assert_eq!(opts.deps, None);
+    assert_eq!(opts.mode, CompileMode::AssembleOnly);
+
+    let opts = parse_args(args(&["slopcc", "-E", "-MMD", "-MF", "a.d", "a.c"]))
+      .expect("-MMD with -MF should be accepted");
+    assert_eq!(opts.dep_output, Some(DepMode::UserHeaders));
+    assert_eq!(opts.dep_file, Some(PathBuf::from("a.d")));
+  }
+
+  #[test]
+  fn parses_dependency_file() {
+    let opts =
+      parse_args(args(&["slopcc", "-M", "-MF", "deps.d", "a.c"])).expect("-MF should be accepted");
+    assert_eq!(opts.dep_file, Some(PathBuf::from("deps.d")));
+
+    let opts =
+      parse_args(args(&["slopcc", "-MM", "-MFout.d", "a.c"])).expect("attached -MF should parse");
+    assert_eq!(opts.dep_file, Some(PathBuf::from("out.d")));
+  }
+
+  #[test]
+  fn dependency_file_requires_dependency_mode() {
+    let err = parse_args(args(&["slopcc", "-MF", "deps.d", "a.c"]))
+      .expect_err("-MF alone should be rejected");
+    assert!(matches!(err, CliError::DepFileWithoutDeps));
+  }
+
+  #[test]
+  fn collects_dependency_targets_in_order() {
+    let opts = parse_args(args(&[
+      "slopcc", "-M", "-MT", "a.o", "-MQ", "$b c.o", "-MTd.o", "a.c",
+    ]))
+    .expect("-MT/-MQ should be accepted");
+    assert_eq!(opts.dep_targets, ["a.o", "$$b\\ c.o", "d.o"]);
+  }
+
+  #[test]
+  fn language_applies_to_later_inputs_only() {
+    let opts = parse_args(args(&[
+      "slopcc", "-E", "a.txt", "-x", "c", "b.unknown", "c.h", "-x", "none", "d.c",
+    ]))
+    .expect("-x should be accepted");
+    assert_eq!(opts.languages, [None, Some(Language::C), Some(Language::C), None]);
+
+    let opts = parse_args(args(&["slopcc", "-xcpp-output", "a.txt"]))
+      .expect("attached -x should be accepted");
+    assert_eq!(opts.languages, [Some(Language::CppOutput)]);
+  }
+
+  #[test]
+  fn rejects_unknown_language() {
+    let err = parse_args(args(&["slopcc", "-x", "fortran", "a.f"]))
+      .expect_err("unknown -x language should be rejected");
+    assert!(matches!(err, CliError::UnknownLanguage(name) if name == "fortran"));
+  }
+
+  #[test]
+  fn detects_language_from_extension() {
+    assert_eq!(Language::from_path(Path::new("a.c")), Some(Language::C));
+    assert_eq!(Language::from_path(Path::new("a.h")), Some(Language::CHeader));
+    assert_eq!(Language::from_path(Path::new("a.i")), Some(Language::CppOutput));
+    assert_eq!(Language::from_path(Path::new("a.o")), None);
+  }
+
+  #[test]
+  fn splits_boolean_short_clusters() {
+    let opts = parse_args(args(&["slopcc", "-cv", "a.c"])).expect("-cv should be accepted");
+    assert_eq!(opts.mode, CompileMode::AssembleOnly);
+    assert!(opts.verbose);
+
+    let opts = parse_args(args(&["slopcc", "-Sv", "a.c"])).expect("-Sv should be accepted");
+    assert_eq!(opts.mode, CompileMode::CompileOnly);
+    assert!(opts.verbose);
+
+    assert_eq!(
+      normalize_gcc_args(args(&["slopcc", "-co", "out.o", "-EIinc"])),
+      args(&["slopcc", "-c", "-o", "out.o", "-E", "-Iinc"])
+    );
+  }
+
+  #[test]
+  fn leaves_value_flags_and_unknown_clusters_alone() {
+    let untouched = args(&["slopcc", "-O2", "-Iinc", "-Wall", "-cq", "-c", "-"]);
+    assert_eq!(normalize_gcc_args(untouched.clone()), untouched);
+  }
+
+  #[test]
+  fn rejects_repeated_output() {
+    let err = parse_args(args(&["slopcc", "-c", "-o", "a.o", "-ob.o", "a.c"]))
+      .expect_err("two -o flags should be rejected");
+    assert!(
+      matches!(err, CliError::RepeatedOutput(ref first, ref second)
+        if first == Path::new("a.o") && second == Path::new("b.o"))
+    );
+    assert_eq!(err.to_string(), "cannot specify -o more than once ('a.o' and 'b.o')");
+  }
+
+  #[test]
+  fn rejects_output_for_multiple_objects() {
+    for mode in ["-c", "-S"] {
+      let err = parse_args(args(&["slopcc", mode, "-o", "out.o", "a.c", "b.c"]))
+        .expect_err("one -o cannot hold two outputs");
+      assert!(matches!(err, CliError::OutputWithMultipleInputs), "{mode}");
+    }
+
+    let opts = parse_args(args(&["slopcc", "-o", "prog", "a.c", "b.c"]))
+      .expect("linking several inputs into one -o is fine");
+    assert_eq!(opts.mode, CompileMode::Link);
+    assert!(parse_args(args(&["slopcc", "-c", "-o", "out.o", "a.c"])).is_ok());
+  }
+
   #[test]
   fn missing_value_is_reported_by_clap() {
     let err =
@@ -187,6 +1302,53 @@ mod tests {
     assert!(matches!(err, CliError::Clap(_)));
   }
 
+  #[test]
+  fn rejects_unknown_options_by_full_name() {
+    let flags = ["-funroll-loops", "-mbogus", "--bogus", "-shared", "-nostdlib", "-Xlinker", "-cq"];
+    for flag in flags {
+      let err = parse_args(args(&["slopcc", flag, "a.c"])).expect_err("unknown option should fail");
+      assert!(matches!(err, CliError::UnknownOption(ref name) if name == flag), "{flag}: {err}");
+    }
+  }
+
+  #[test]
+  fn takes_dash_leading_option_values_as_values() {
+    let opts = parse_args(args(&["slopcc", "-E", "-o", "-mout.i", "-D", "-fbar", "a.c"]))
+      .expect("option values may start with a dash");
+    assert_eq!(opts.output, Some(PathBuf::from("-mout.i")));
+    assert_eq!(opts.macros, [MacroDef::define("-fbar")]);
+    assert_eq!(opts.inputs, [PathBuf::from("a.c")]);
+
+    let opts = parse_args(args(&["slopcc", "-co", "-fPIC", "a.c"]))
+      .expect("a clustered -o takes the next argument");
+    assert_eq!(opts.output, Some(PathBuf::from("-fPIC")));
+    assert!(opts.noop_options.is_empty());
+  }
+
+  #[test]
+  fn accepts_noop_flags_for_cc_compatibility() {
+    let opts = parse_args(args(&[
+      "slopcc", "-pipe", "-fPIC", "-pthread", "-march=native", "-Wl,-O1", "-fuse-ld=lld", "a.c",
+    ]))
+    .expect("no-op flags should be accepted");
+    assert_eq!(
+      opts.noop_options,
+      ["-pipe", "-fPIC", "-pthread", "-march=native", "-Wl,-O1", "-fuse-ld=lld"]
+    );
+    assert!(opts.ignored_options.is_empty());
+    assert_eq!(opts.inputs, [PathBuf::from("a.c")]);
+  }
+
+  #[test]
+  fn ignores_unknown_warning_options() {
+    let opts = parse_args(args(&["slopcc", "-Wfoo", "-Wno-bar", "-Wall", "a.c"]))
+      .expect("unknown -W options should only warn");
+    assert_eq!(opts.ignored_options, ["-Wfoo", "-Wno-bar"]);
+    let mut expected = WarningConfig::default();
+    expected.apply_flag("all");
+    assert_eq!(opts.warnings, expected);
+  }
+
   #[test]
   fn reports_missing_inputs_without_version() {
     let err =
@@ -0,0 +1,189 @@
+use std::path::{
+  Path,
+  PathBuf,
+};
+
+use slopcc_common::prelude::{
+  FileId,
+  SourceMap,
+};
+use slopcc_lex::{
+  LexOptions,
+  Lexer,
+  TokenKind,
+};
+
+use crate::{
+  cli::DepMode,
+  include::IncludeResolver,
+};
+
+/// An `#include` directive found by scanning a file's tokens.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Include {
+  pub name: String,
+  pub angled: bool,
+}
+
+/// Finds `#include <...>` and `#include "..."` directives. Conditional
+/// directives are not evaluated, so every include in the file is reported.
+#[must_use]
+pub fn scan_includes(src: &[u8], file: FileId) -> Vec<Include> {
+  let options = LexOptions {
+    significant_newlines: true,
+    ..LexOptions::default()
+  };
+  let mut lexer = Lexer::with_options(src, file, options);
+  let mut includes = Vec::new();
+
+  loop {
+    let hash = lexer.next_significant();
+    if hash.kind == TokenKind::Eof {
+      break;
+    }
+    if hash.kind != TokenKind::Hash || !hash.bol {
+      continue;
+    }
+
+    let directive = lexer.next_significant();
+    if directive.kind != TokenKind::Ident || lexer.text(directive) != b"include" {
+      continue;
+    }
+
+    lexer.expect_header_name();
+    let header = lexer.next_significant();
+    if header.kind != TokenKind::HeaderName {
+      continue;
+    }
+
+    let text = lexer.text(header);
+    includes.push(Include {
+      name: String::from_utf8_lossy(&text[1..text.len() - 1]).into_owned(),
+      angled: text[0] == b'<',
+    });
+  }
+
+  includes
+}
+
+/// Lists `main` followed by every header it transitively includes, in
+/// discovery order. Headers that cannot be found are skipped; `-MM` also
+/// drops (and does not descend into) headers found in system directories,
+/// `-isystem` ones included.
+pub fn collect_dependencies(
+  sources: &mut SourceMap,
+  main: FileId,
+  main_path: &Path,
+  resolver: &IncludeResolver,
+  mode: DepMode,
+) -> Vec<PathBuf> {
+  let mut deps = vec![main_path.to_path_buf()];
+  let mut pending = vec![main];
+
+  while let Some(file) = pending.pop() {
+    let source = sources.file(file);
+    let current_dir = source.path().and_then(Path::parent);
+    let includes = scan_includes(source.bytes(), file);
+    let resolved: Vec<_> = includes
+      .iter()
+      .filter_map(|include| resolver.resolve(&include.name, include.angled, current_dir))
+      .collect();
+
+    for found in resolved {
+      if deps.contains(&found.path) || (found.system && mode == DepMode::UserHeaders) {
+        continue;
+      }
+      if let Ok(id) = sources.add_file_from_path(&found.path) {
+        pending.push(id);
+      }
+      deps.push(found.path);
+    }
+  }
+
+  deps
+}
+
+/// Formats a make rule `targets: prerequisites`.
+#[must_use]
+pub fn format_rule(targets: &[String], prerequisites: &[PathBuf]) -> String {
+  let mut rule = targets.join(" ");
+  rule.push(':');
+  for prerequisite in prerequisites {
+    rule.push(' ');
+    rule.push_str(&prerequisite.display().to_string());
+  }
+  rule.push('\n');
+  rule
+}
+
+/// Quotes a rule target the way `-MQ` does: `$` becomes `$$`, `#` and
+/// blanks are backslash-escaped, and backslashes before a blank are doubled.
+#[must_use]
+pub fn quote_target(target: &str) -> String {
+  let mut quoted = String::with_capacity(target.len());
+  let mut backslashes = 0;
+  for c in target.chars() {
+    match c {
+      ' ' | '\t' => quoted.push_str(&"\\".repeat(backslashes + 1)),
+      '$' => quoted.push('$'),
+      '#' => quoted.push('\\'),
+      _ => {}
+    }
+    backslashes = if c == '\\' { backslashes + 1 } else { 0 };
+    quoted.push(c);
+  }
+  quoted
+}
+
+#[cfg(test)]
+mod tests {
+  use std::path::PathBuf;
+
+  use super::{
+    format_rule,
+    quote_target,
+    scan_includes,
+    Include,
+  };
+  use slopcc_common::prelude::FileId;
+
+  #[test]
+  fn scans_angled_and_quoted_includes() {
+    let src = b"#include <stdio.h>\n  #  include\t\"local.h\"\nint x; # include <no.h>\n\
+      #include /* c */ \"header.h\"\n#include\n<not.h>\n";
+    let includes = scan_includes(src, FileId::new_for_tests(0));
+    assert_eq!(
+      includes,
+      vec![
+        Include {
+          name: String::from("stdio.h"),
+          angled: true,
+        },
+        Include {
+          name: String::from("local.h"),
+          angled: false,
+        },
+        Include {
+          name: String::from("header.h"),
+          angled: false,
+        },
+      ]
+    );
+  }
+
+  #[test]
+  fn formats_rule_with_target_and_prerequisites() {
+    let rule = format_rule(
+      &[String::from("foo.o")],
+      &[PathBuf::from("foo.c"), PathBuf::from("header.h")],
+    );
+    assert_eq!(rule, "foo.o: foo.c header.h\n");
+  }
+
+  #[test]
+  fn quotes_make_special_characters() {
+    assert_eq!(quote_target("plain.o"), "plain.o");
+    assert_eq!(quote_target("$(OBJ) #1"), "$$(OBJ)\\ \\#1");
+    assert_eq!(quote_target("a\\ b"), "a\\\\\\ b");
+  }
+}
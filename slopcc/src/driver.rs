@@ -1,33 +1,778 @@
-use std::path::Path;
+use std::{
+  ffi::OsString,
+  fs,
+  io::{
+    ErrorKind,
+    IsTerminal,
+    Read,
+    Write,
+  },
+  path::{
+    Path,
+    PathBuf,
+  },
+};
 
-use slopcc_common::prelude::SourceMap;
+use slopcc_arena::prelude::{
+  Arena,
+  Interner,
+};
+use slopcc_common::prelude::{
+  Diagnostics,
+  FileId,
+  Renderer,
+  Severity,
+  SourceError,
+  SourceFile,
+  SourceMap,
+};
+use slopcc_lex::{
+  LexOptions,
+  Lexer,
+  Token,
+};
 
-use crate::cli::CliOptions;
+use crate::{
+  cli::{
+    self,
+    CliOptions,
+    ColorChoice,
+    CompileMode,
+    DepMode,
+    Language,
+    StdVersion,
+  },
+  deps,
+  emit::Emitter,
+  include::IncludeResolver,
+  macros,
+};
 
 #[derive(thiserror::Error, Debug)]
 pub enum DriverError {
-  #[error("{0}")]
-  Source(#[from] slopcc_common::prelude::SourceError),
-  #[error("tokenizer phase is not implemented yet")]
-  TokenizerNotImplemented,
+  #[error("{}: {}", .path.display(), io_reason(.source))]
+  ReadInput {
+    path: PathBuf,
+    source: std::io::Error,
+  },
+  #[error("failed to write output file '{path}': {source}")]
+  WriteOutput {
+    path: PathBuf,
+    source: std::io::Error,
+  },
+  #[error("failed to read standard input: {0}")]
+  ReadStdin(std::io::Error),
+  #[error("failed to write to standard output: {0}")]
+  WriteStdout(std::io::Error),
+  #[error("{}: No such file or directory", .0.display())]
+  ForcedIncludeNotFound(PathBuf),
+  #[error("preprocessing failed")]
+  PreprocessFailed,
+  #[error("errors detected in input")]
+  CheckFailed,
+  #[error("compilation beyond preprocessing (-E) is not implemented yet")]
+  CompileNotImplemented,
+}
+
+impl From<SourceError> for DriverError {
+  fn from(error: SourceError) -> Self {
+    match error {
+      SourceError::ReadFile { path, source } => Self::ReadInput { path, source },
+    }
+  }
+}
+
+/// GCC-style text for the I/O failures users hit most, falling back to the
+/// OS message.
+fn io_reason(error: &std::io::Error) -> String {
+  match error.kind() {
+    ErrorKind::NotFound => String::from("No such file or directory"),
+    ErrorKind::PermissionDenied => String::from("Permission denied"),
+    ErrorKind::IsADirectory => String::from("Is a directory"),
+    _ => error.to_string(),
+  }
 }
 
 pub fn run(options: &CliOptions) -> Result<(), DriverError> {
+  run_with_io(options, &mut std::io::stdin().lock(), &mut std::io::stdout().lock())
+}
+
+/// Lexes `path` the way `-E` would, after any `-include` files, and returns
+/// the sources, the tokens and the diagnostics kept by the warning flags
+/// instead of printing anything. The tokens of each forced include come
+/// first, without their `Eof`; the last token is the input's `Eof`.
+pub fn preprocess_file(
+  path: &Path,
+  options: &CliOptions,
+) -> Result<(SourceMap, Vec<Token>, Diagnostics), DriverError> {
+  let mut sources = SourceMap::new();
+  let file = sources.add_file_from_path(path)?;
+  let forced = load_forced_includes(options, &mut sources)?;
+
+  let mut tokens = Vec::new();
+  let mut diagnostics = Diagnostics::new();
+  for &id in forced.iter().chain([&file]) {
+    let src = sources.file(id).bytes();
+    let mut lexer = Lexer::with_options(src, id, lex_options(options));
+    tokens.extend(lexer.by_ref().filter(|token| id == file || !token.kind.is_eof()));
+    diagnostics.extend(options.warnings.filter(&lexer.take_diagnostics()));
+  }
+  Ok((sources, tokens, diagnostics))
+}
+
+/// Runs the driver with explicit standard streams. An input of `-` reads
+/// `stdin`; `-o -` and default `-E` output go to `stdout`.
+pub fn run_with_io(
+  options: &CliOptions,
+  stdin: &mut dyn Read,
+  stdout: &mut dyn Write,
+) -> Result<(), DriverError> {
+  if options.show_help {
+    stdout.write_all(cli::USAGE.as_bytes()).map_err(DriverError::WriteStdout)?;
+    return Ok(());
+  }
+
   if options.show_version {
-    println!("slopcc {}", env!("CARGO_PKG_VERSION"));
+    writeln!(stdout, "slopcc {}", env!("CARGO_PKG_VERSION")).map_err(DriverError::WriteStdout)?;
+    return Ok(());
+  }
+
+  if options.dump_machine {
+    writeln!(stdout, "{}", target_triple()).map_err(DriverError::WriteStdout)?;
     return Ok(());
   }
 
+  if let Some(name) = &options.print_file_name {
+    let path = find_library_file(options, name);
+    writeln!(stdout, "{}", path.display()).map_err(DriverError::WriteStdout)?;
+    return Ok(());
+  }
+
+  if options.verbose {
+    eprintln!("slopcc version {}", env!("CARGO_PKG_VERSION"));
+    for option in &options.noop_options {
+      eprintln!(" ignoring {option}");
+    }
+  }
+  for option in &options.ignored_options {
+    eprintln!("slopcc: warning: unrecognized command-line option '{option}'; ignored");
+  }
+
   let mut sources = SourceMap::new();
+  let mut files = Vec::with_capacity(options.inputs.len());
   for input in &options.inputs {
-    sources.add_file_from_path(Path::new(input))?;
+    let file = if is_stdio(input) {
+      let mut bytes = Vec::new();
+      stdin
+        .read_to_end(&mut bytes)
+        .map_err(DriverError::ReadStdin)?;
+      sources.add_stdin(bytes)
+    } else {
+      sources.add_file_from_path(input)?
+    };
+    files.push(file);
   }
 
   if options.dry_run {
     for input in &options.inputs {
-      println!("would compile: {}", input.display());
+      let output = match (&options.output, options.mode) {
+        (Some(output), _) => Some(output.clone()),
+        (None, CompileMode::PreprocessOnly) => None,
+        (None, mode) => Some(default_output(input, mode)),
+      };
+      match output {
+        Some(output) => writeln!(
+          stdout,
+          "would compile: {} -> {}",
+          input.display(),
+          output.display()
+        ),
+        None => writeln!(stdout, "would compile: {}", input.display()),
+      }
+      .map_err(DriverError::WriteStdout)?;
+    }
+  }
+
+  if let Some(dep_mode) = options.deps {
+    return write_dependencies(options, &mut sources, &files, dep_mode, stdout);
+  }
+
+  if options.syntax_only {
+    let forced = load_forced_includes(options, &mut sources)?;
+    return check_syntax(options, &sources, &forced, &files);
+  }
+
+  match options.mode {
+    CompileMode::PreprocessOnly if options.dump_macros => {
+      write_output(options, macros::dump_macros(options).as_bytes(), stdout)?;
+    }
+    CompileMode::PreprocessOnly => {
+      let forced = load_forced_includes(options, &mut sources)?;
+      preprocess(options, &sources, &forced, &files, stdout)?;
+    }
+    CompileMode::CompileOnly | CompileMode::AssembleOnly | CompileMode::Link => {
+      if options.save_temps {
+        let forced = load_forced_includes(options, &mut sources)?;
+        save_temps(options, &sources, &forced, &files)?;
+      }
+      return Err(DriverError::CompileNotImplemented);
+    }
+  }
+
+  if let Some(dep_mode) = options.dep_output {
+    write_dependency_files(options, &mut sources, &files, dep_mode)?;
+  }
+  Ok(())
+}
+
+/// Directories the link step searches after `-L`.
+const BUILTIN_LIBRARY_DIRS: &[&str] = &["/usr/local/lib", "/usr/lib"];
+
+/// GCC-style triple for the host, which is also the only target.
+fn target_triple() -> String {
+  let arch = std::env::consts::ARCH;
+  match std::env::consts::OS {
+    "linux" => format!("{arch}-linux-gnu"),
+    "macos" => format!("{arch}-apple-darwin"),
+    os => format!("{arch}-unknown-{os}"),
+  }
+}
+
+/// `-print-file-name`: the first `-L` or built-in library dir holding
+/// `name`; GCC prints the name unchanged when nothing matches.
+fn find_library_file(options: &CliOptions, name: &str) -> PathBuf {
+  let builtin = BUILTIN_LIBRARY_DIRS.iter().map(Path::new);
+  options
+    .lib_dirs
+    .iter()
+    .map(PathBuf::as_path)
+    .chain(builtin)
+    .map(|dir| dir.join(name))
+    .find(|path| path.exists())
+    .unwrap_or_else(|| PathBuf::from(name))
+}
+
+/// `-` names standard input as an input and standard output as `-o`.
+fn is_stdio(path: &Path) -> bool {
+  path.as_os_str() == "-"
+}
+
+/// Writes one make rule per input in place of preprocessed output (`-M`/`-MM`),
+/// to the `-MF` file when given.
+fn write_dependencies(
+  options: &CliOptions,
+  sources: &mut SourceMap,
+  files: &[FileId],
+  dep_mode: DepMode,
+  stdout: &mut dyn Write,
+) -> Result<(), DriverError> {
+  let mut out = String::new();
+  for (input, &file) in options.inputs.iter().zip(files) {
+    let target = default_output(input, CompileMode::AssembleOnly);
+    out.push_str(&dependency_rule(options, sources, input, file, target, dep_mode));
+  }
+  match &options.dep_file {
+    Some(path) => write_file(path, out.as_bytes()),
+    None => write_output(options, out.as_bytes(), stdout),
+  }
+}
+
+/// Writes `.d` files next to the normal output (`-MD`/`-MMD`). Each input
+/// gets its own file named after `-o` or the input unless `-MF` collects
+/// every rule into one.
+fn write_dependency_files(
+  options: &CliOptions,
+  sources: &mut SourceMap,
+  files: &[FileId],
+  dep_mode: DepMode,
+) -> Result<(), DriverError> {
+  let output = options.output.as_ref().filter(|path| !is_stdio(path));
+  let mut combined = String::new();
+  for (input, &file) in options.inputs.iter().zip(files) {
+    let target = output
+      .cloned()
+      .unwrap_or_else(|| default_output(input, CompileMode::AssembleOnly));
+    let rule = dependency_rule(options, sources, input, file, target, dep_mode);
+    if options.dep_file.is_some() {
+      combined.push_str(&rule);
+      continue;
+    }
+    let path = match output {
+      Some(output) => output.with_extension("d"),
+      None => output_name(input, "d"),
+    };
+    write_file(&path, rule.as_bytes())?;
+  }
+  match &options.dep_file {
+    Some(path) => write_file(path, combined.as_bytes()),
+    None => Ok(()),
+  }
+}
+
+/// Formats the make rule for one input. `-MT`/`-MQ` replace `target`.
+fn dependency_rule(
+  options: &CliOptions,
+  sources: &mut SourceMap,
+  input: &Path,
+  file: FileId,
+  target: PathBuf,
+  dep_mode: DepMode,
+) -> String {
+  if options.verbose {
+    eprintln!(" scanning dependencies of {}", input.display());
+  }
+  let targets = if options.dep_targets.is_empty() {
+    vec![target.display().to_string()]
+  } else {
+    options.dep_targets.clone()
+  };
+  let resolver = IncludeResolver::from_options(options);
+  let prerequisites = deps::collect_dependencies(sources, file, input, &resolver, dep_mode);
+  deps::format_rule(&targets, &prerequisites)
+}
+
+/// Writes `out` to `path` through a temporary file in the same directory and
+/// a rename, so an interrupted or failed write never leaves a partial file.
+/// Missing parent directories are created first. A symlink is resolved and
+/// its target replaced; devices, FIFOs and dangling links are written in
+/// place, since renaming over them would replace the entry itself.
+fn write_file(path: &Path, out: &[u8]) -> Result<(), DriverError> {
+  let error = |source| DriverError::WriteOutput {
+    path: path.to_path_buf(),
+    source,
+  };
+  if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+    fs::create_dir_all(parent).map_err(error)?;
+  }
+
+  let is_link = fs::symlink_metadata(path).is_ok_and(|meta| meta.file_type().is_symlink());
+  let target = if is_link {
+    fs::canonicalize(path).ok()
+  } else {
+    Some(path.to_path_buf())
+  };
+  let Some(target) = target.filter(|target| is_replaceable(target)) else {
+    return fs::write(path, out).map_err(error);
+  };
+
+  let temp = temp_path(&target);
+  fs::write(&temp, out)
+    .and_then(|()| fs::rename(&temp, &target))
+    .map_err(|source| {
+      let _ = fs::remove_file(&temp);
+      error(source)
+    })
+}
+
+/// Whether [`write_file`] may rename a new file over `path`: it is missing
+/// or a regular file.
+fn is_replaceable(path: &Path) -> bool {
+  match fs::metadata(path) {
+    Ok(meta) => meta.is_file(),
+    Err(error) => error.kind() == ErrorKind::NotFound,
+  }
+}
+
+/// Hidden sibling of `path` for [`write_file`], unique per process.
+fn temp_path(path: &Path) -> PathBuf {
+  let mut name = OsString::from(".");
+  name.push(path.file_name().unwrap_or_default());
+  name.push(format!(".{}.tmp", std::process::id()));
+  path.with_file_name(name)
+}
+
+fn write_output(
+  options: &CliOptions,
+  out: &[u8],
+  stdout: &mut dyn Write,
+) -> Result<(), DriverError> {
+  match &options.output {
+    Some(path) if !is_stdio(path) => write_file(path, out),
+    _ => stdout.write_all(out).map_err(DriverError::WriteStdout),
+  }
+}
+
+/// Output path GCC would pick when `-o` is absent: the input's file name with
+/// its extension replaced (`.i`, `.s`, `.o`) in the current directory, or
+/// `a.out` when linking. `-E` itself still defaults to stdout; `.i` is the
+/// name used for kept intermediates.
+#[must_use]
+pub fn default_output(input: &Path, mode: CompileMode) -> PathBuf {
+  let extension = match mode {
+    CompileMode::PreprocessOnly => "i",
+    CompileMode::CompileOnly => "s",
+    CompileMode::AssembleOnly => "o",
+    CompileMode::Link => return PathBuf::from("a.out"),
+  };
+  output_name(input, extension)
+}
+
+/// `input`'s file name with its extension replaced, in the current directory.
+fn output_name(input: &Path, extension: &str) -> PathBuf {
+  let mut output = input.file_stem().unwrap_or(input.as_os_str()).to_os_string();
+  output.push(".");
+  output.push(extension);
+  PathBuf::from(output)
+}
+
+/// Loads the `-include` files, searched like `#include "..."` written in a
+/// file in the current directory.
+fn load_forced_includes(
+  options: &CliOptions,
+  sources: &mut SourceMap,
+) -> Result<Vec<FileId>, DriverError> {
+  let resolver = IncludeResolver::from_options(options);
+  let mut forced = Vec::with_capacity(options.forced_includes.len());
+  for name in &options.forced_includes {
+    let found = resolver
+      .resolve(&name.display().to_string(), false, Some(Path::new("")))
+      .ok_or_else(|| DriverError::ForcedIncludeNotFound(name.clone()))?;
+    forced.push(sources.add_file_from_path(&found.path)?);
+  }
+  Ok(forced)
+}
+
+/// Writes each input's tokens, preceded by the `forced` includes' tokens.
+fn preprocess(
+  options: &CliOptions,
+  sources: &SourceMap,
+  forced: &[FileId],
+  files: &[FileId],
+  stdout: &mut dyn Write,
+) -> Result<(), DriverError> {
+  let mut out = Vec::new();
+  let failed = lex_inputs(options, sources, forced, files, Some(&mut out)).failed();
+
+  // Like GCC, a failed run still prints to stdout but leaves no `-o` file.
+  let to_file = options.output.as_deref().is_some_and(|path| !is_stdio(path));
+  if !(failed && to_file) {
+    write_output(options, &out, stdout)?;
+  }
+
+  if failed {
+    return Err(DriverError::PreprocessFailed);
+  }
+  Ok(())
+}
+
+/// `-fsyntax-only`: lexes every input and reports diagnostics without
+/// writing anything.
+fn check_syntax(
+  options: &CliOptions,
+  sources: &SourceMap,
+  forced: &[FileId],
+  files: &[FileId],
+) -> Result<(), DriverError> {
+  if lex_inputs(options, sources, forced, files, None).failed() {
+    return Err(DriverError::CheckFailed);
+  }
+  Ok(())
+}
+
+/// Lexes each C input after the `forced` includes, appending the spelling to
+/// `out` when given, and reports the kept diagnostics followed by their
+/// summary.
+fn lex_inputs(
+  options: &CliOptions,
+  sources: &SourceMap,
+  forced: &[FileId],
+  files: &[FileId],
+  mut out: Option<&mut Vec<u8>>,
+) -> Tally {
+  let mut interner = Interner::with_arena(new_arena());
+  let mut tally = Tally::default();
+  for (index, &file) in files.iter().enumerate() {
+    tally += lex_input(options, sources, forced, index, file, &mut interner, out.as_deref_mut());
+  }
+  report_summary(options, tally);
+  tally
+}
+
+/// [`lex_inputs`] for the single input at `index`.
+fn lex_input(
+  options: &CliOptions,
+  sources: &SourceMap,
+  forced: &[FileId],
+  index: usize,
+  file: FileId,
+  interner: &mut Interner,
+  mut out: Option<&mut Vec<u8>>,
+) -> Tally {
+  if input_language(options, index).is_none() {
+    eprintln!(
+      "slopcc: warning: {}: linker input file unused because linking not done",
+      options.inputs[index].display()
+    );
+    return Tally::default();
+  }
+  if options.verbose {
+    let phase = if out.is_some() { "preprocessing" } else { "checking" };
+    eprintln!(" {phase} {}", options.inputs[index].display());
+  }
+
+  let mut tally = Tally::default();
+  for &file in forced.iter().chain([&file]) {
+    let src = sources.file(file).bytes();
+    if let Some(out) = out.as_deref_mut().filter(|_| options.line_markers) {
+      write_line_marker(out, sources.file(file));
+    }
+    let mut lexer = Lexer::with_options(src, file, lex_options(options)).with_interner(interner);
+    let mut emitter = out
+      .as_deref_mut()
+      .map(|out| Emitter::new(out, options.preserve_comments, lex_options(options)));
+    for token in lexer.by_ref() {
+      if let Some(emitter) = emitter.as_mut() {
+        emitter.token(token, token.span.as_bytes(src));
+      }
+    }
+    if let Some(emitter) = emitter {
+      emitter.finish();
+    }
+    let diagnostics = options.warnings.filter(&lexer.take_diagnostics());
+    tally.count(&diagnostics);
+    report(options, sources, &diagnostics);
+  }
+  tally
+}
+
+/// `-save-temps`: keeps each C source's preprocessed form as `<stem>.i` in
+/// the current directory. The `.s` stage joins it once code generation
+/// exists.
+fn save_temps(
+  options: &CliOptions,
+  sources: &SourceMap,
+  forced: &[FileId],
+  files: &[FileId],
+) -> Result<(), DriverError> {
+  let mut interner = Interner::with_arena(new_arena());
+  let mut tally = Tally::default();
+  for (index, &file) in files.iter().enumerate() {
+    if input_language(options, index) != Some(Language::C) {
+      continue;
+    }
+    let mut out = Vec::new();
+    let input = lex_input(options, sources, forced, index, file, &mut interner, Some(&mut out));
+    tally += input;
+    if input.failed() {
+      report_summary(options, tally);
+      return Err(DriverError::PreprocessFailed);
+    }
+    let path = default_output(&options.inputs[index], CompileMode::PreprocessOnly);
+    write_file(&path, &out)?;
+  }
+  report_summary(options, tally);
+  Ok(())
+}
+
+/// Arena for a compilation's long-lived data. `SLOPCC_ARENA_CHUNK` sets the
+/// chunk size in bytes, for profiling; unset or invalid values keep the
+/// default.
+fn new_arena() -> Arena {
+  let chunk_size = std::env::var("SLOPCC_ARENA_CHUNK").ok();
+  match arena_chunk_size(chunk_size.as_deref()) {
+    Some(size) => Arena::builder().chunk_size(size).build(),
+    None => Arena::new(),
+  }
+}
+
+/// Parses a `SLOPCC_ARENA_CHUNK` value: a positive byte count.
+fn arena_chunk_size(value: Option<&str>) -> Option<usize> {
+  value?.trim().parse().ok().filter(|&size| size > 0)
+}
+
+/// Lexer settings implied by the command line.
+fn lex_options(options: &CliOptions) -> LexOptions {
+  LexOptions {
+    c90: matches!(options.std, StdVersion::C89 | StdVersion::Gnu89),
+    dollar_idents: options.std.is_gnu(),
+    u8_char_consts: matches!(options.std, StdVersion::C23 | StdVersion::Gnu23),
+    digit_separators: options.std.is_gnu() || options.std == StdVersion::C23,
+    ..LexOptions::default()
+  }
+}
+
+/// Language of the input at `index`: the `-x` in effect, else its extension.
+/// Standard input is C unless `-x` says otherwise.
+fn input_language(options: &CliOptions, index: usize) -> Option<Language> {
+  let input = &options.inputs[index];
+  options.languages[index].or_else(|| {
+    if is_stdio(input) {
+      Some(Language::C)
+    } else {
+      Language::from_path(input)
+    }
+  })
+}
+
+/// Appends GCC's `# 1 "name"` marker for the start of `source`. Every file
+/// is reprinted line for line, so one marker per file keeps the lines right.
+fn write_line_marker(out: &mut Vec<u8>, source: &SourceFile) {
+  let name = source.name().to_string();
+  let escaped = name.replace('\\', "\\\\").replace('"', "\\\"");
+  out.extend_from_slice(format!("# 1 \"{escaped}\"\n").as_bytes());
+}
+
+/// Prints each diagnostic with its location and source lines, as
+/// [`Renderer::report`] formats them.
+fn report(options: &CliOptions, sources: &SourceMap, diagnostics: &Diagnostics) {
+  let renderer = Renderer::new(use_color(options.color));
+  for diagnostic in diagnostics.iter() {
+    eprint!("{}", renderer.report(diagnostic, sources));
+  }
+}
+
+/// Prints the closing `N errors, M warnings generated` line, unless nothing
+/// was reported or `-w` is given.
+fn report_summary(options: &CliOptions, tally: Tally) {
+  if options.warnings.suppress_all {
+    return;
+  }
+  if let Some(summary) = tally.summary() {
+    eprintln!("{summary}");
+  }
+}
+
+/// Errors and warnings reported so far in a run.
+#[derive(Copy, Clone, Default, Eq, PartialEq, Debug)]
+struct Tally {
+  errors: usize,
+  warnings: usize,
+}
+
+impl Tally {
+  /// Adds the errors and warnings in `diagnostics`; notes are not counted.
+  fn count(&mut self, diagnostics: &Diagnostics) {
+    for diagnostic in diagnostics.iter() {
+      match diagnostic.severity {
+        Severity::Error => self.errors += 1,
+        Severity::Warning => self.warnings += 1,
+        Severity::Note => {}
+      }
     }
   }
 
-  Err(DriverError::TokenizerNotImplemented)
+  fn failed(self) -> bool {
+    self.errors > 0
+  }
+
+  /// `2 errors, 1 warning generated`, leaving out a zero count; `None` when
+  /// both are zero.
+  fn summary(self) -> Option<String> {
+    let parts: Vec<String> = [(self.errors, "error"), (self.warnings, "warning")]
+      .into_iter()
+      .filter(|&(count, _)| count > 0)
+      .map(|(count, noun)| format!("{count} {noun}{}", if count == 1 { "" } else { "s" }))
+      .collect();
+    (!parts.is_empty()).then(|| format!("{} generated", parts.join(", ")))
+  }
+}
+
+impl std::ops::AddAssign for Tally {
+  fn add_assign(&mut self, other: Self) {
+    self.errors += other.errors;
+    self.warnings += other.warnings;
+  }
+}
+
+fn use_color(choice: ColorChoice) -> bool {
+  match choice {
+    ColorChoice::Auto => std::io::stderr().is_terminal(),
+    ColorChoice::Always => true,
+    ColorChoice::Never => false,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::path::{
+    Path,
+    PathBuf,
+  };
+
+  use super::{
+    arena_chunk_size,
+    default_output,
+    run_with_io,
+    Tally,
+  };
+  use crate::cli::{
+    parse_args,
+    CliOptions,
+    CompileMode,
+  };
+
+  fn options(items: &[&str]) -> CliOptions {
+    parse_args(items.iter().map(std::ffi::OsString::from)).expect("test arguments should parse")
+  }
+
+  #[test]
+  fn default_output_replaces_extension_per_mode() {
+    let input = Path::new("src/foo.c");
+    assert_eq!(default_output(input, CompileMode::AssembleOnly), PathBuf::from("foo.o"));
+    assert_eq!(default_output(input, CompileMode::CompileOnly), PathBuf::from("foo.s"));
+    assert_eq!(default_output(input, CompileMode::PreprocessOnly), PathBuf::from("foo.i"));
+  }
+
+  #[test]
+  fn default_output_links_to_a_out() {
+    assert_eq!(default_output(Path::new("foo.c"), CompileMode::Link), PathBuf::from("a.out"));
+  }
+
+  #[test]
+  fn default_output_handles_odd_extensions() {
+    assert_eq!(default_output(Path::new("foo"), CompileMode::AssembleOnly), PathBuf::from("foo.o"));
+    assert_eq!(
+      default_output(Path::new("foo.bar.txt"), CompileMode::AssembleOnly),
+      PathBuf::from("foo.bar.o")
+    );
+    assert_eq!(
+      default_output(Path::new(".hidden"), CompileMode::CompileOnly),
+      PathBuf::from(".hidden.s")
+    );
+  }
+
+  #[test]
+  fn missing_input_names_path_and_reason() {
+    let mut stdin: &[u8] = b"";
+    let mut stdout = Vec::new();
+    let err = run_with_io(&options(&["slopcc", "-E", "no/such/input.c"]), &mut stdin, &mut stdout)
+      .expect_err("missing input should fail");
+    assert_eq!(err.to_string(), "no/such/input.c: No such file or directory");
+  }
+
+  #[test]
+  fn dash_input_reads_stdin() {
+    let mut stdin: &[u8] = b"int x; /* c */\n";
+    let mut stdout = Vec::new();
+    run_with_io(&options(&["slopcc", "-E", "-"]), &mut stdin, &mut stdout)
+      .expect("stdin input should preprocess");
+    assert_eq!(stdout, b"# 1 \"<stdin>\"\nint x;\n");
+  }
+
+  #[test]
+  fn dash_output_writes_stdout() {
+    let mut stdin: &[u8] = b"a b\n";
+    let mut stdout = Vec::new();
+    run_with_io(&options(&["slopcc", "-E", "-P", "-", "-o", "-"]), &mut stdin, &mut stdout)
+      .expect("-o - should write to stdout");
+    assert_eq!(stdout, b"a b\n");
+  }
+
+  #[test]
+  fn arena_chunk_size_accepts_only_positive_byte_counts() {
+    assert_eq!(arena_chunk_size(Some("65536")), Some(65536));
+    assert_eq!(arena_chunk_size(Some(" 512\n")), Some(512));
+    assert_eq!(arena_chunk_size(Some("0")), None);
+    assert_eq!(arena_chunk_size(Some("64k")), None);
+    assert_eq!(arena_chunk_size(None), None);
+  }
+
+  #[test]
+  fn summary_pluralizes_and_skips_zero_counts() {
+    let tally = |errors, warnings| Tally { errors, warnings }.summary();
+    assert_eq!(tally(0, 0), None);
+    assert_eq!(tally(1, 0).as_deref(), Some("1 error generated"));
+    assert_eq!(tally(0, 3).as_deref(), Some("3 warnings generated"));
+    assert_eq!(tally(2, 1).as_deref(), Some("2 errors, 1 warning generated"));
+  }
 }
@@ -0,0 +1,262 @@
+use slopcc_common::prelude::{
+  FileId,
+  Span,
+};
+use slopcc_lex::{
+  LexOptions,
+  Lexer,
+  Token,
+  TokenKind,
+};
+
+/// Writes a token stream back out as `-E` text. Line structure and leading
+/// indentation are kept; any other run of whitespace or dropped comments
+/// becomes one space. Tokens that arrive with nothing between them get a
+/// space only when their spellings would otherwise lex differently
+/// (`+` `+`, `a` `1`, `.` `5`, or the third `.` of `.` `.` `.`). Tokens that
+/// were already adjacent in their source lexed apart there, so only other
+/// adjacencies are re-lexed.
+pub struct Emitter<'out, 'src> {
+  out: &'out mut Vec<u8>,
+  preserve_comments: bool,
+  /// The grammar the input was lexed with, used to check for pastes.
+  lex_options: LexOptions,
+  line_start: bool,
+  pending_space: bool,
+  /// Newlines inside dropped comments, written at the end of the line so
+  /// later lines keep their numbers.
+  pending_newlines: usize,
+  prev: Option<(Span, &'src [u8])>,
+  /// The token before `prev` when nothing separates the two, since `..`
+  /// only pastes once a third `.` follows.
+  before_prev: Option<(Span, &'src [u8])>,
+}
+
+impl<'out, 'src> Emitter<'out, 'src> {
+  /// Appends to `out`, which must be empty or end a line. Comments are kept
+  /// verbatim with `preserve_comments` (`-C`) and otherwise count as
+  /// whitespace. `lex_options` should match the lexer that produced the
+  /// tokens.
+  pub fn new(out: &'out mut Vec<u8>, preserve_comments: bool, lex_options: LexOptions) -> Self {
+    Self {
+      out,
+      preserve_comments,
+      lex_options,
+      line_start: true,
+      pending_space: false,
+      pending_newlines: 0,
+      prev: None,
+      before_prev: None,
+    }
+  }
+
+  /// Writes `token`, spelled `text`.
+  pub fn token(&mut self, token: Token, text: &'src [u8]) {
+    match token.kind {
+      TokenKind::Eof => {}
+      TokenKind::Newline => self.newline(),
+      TokenKind::Whitespace if self.line_start => self.out.extend_from_slice(text),
+      TokenKind::Whitespace => self.pending_space = true,
+      TokenKind::Comment if !self.preserve_comments => {
+        self.pending_newlines += text.iter().filter(|&&byte| byte == b'\n').count();
+        if self.line_start {
+          self.out.push(b' ');
+        } else {
+          self.pending_space = true;
+        }
+      }
+      _ => {
+        let separate = self.pending_space
+          || self.prev.is_some_and(|(prev_span, prev)| {
+            let joined = adjacent(prev_span, token.span);
+            (!joined && self.pastes(&[prev, text]))
+              || self.before_prev.is_some_and(|(before_span, before)| {
+                !(joined && adjacent(before_span, prev_span))
+                  && self.pastes(&[before, prev, text])
+              })
+          });
+        if separate {
+          self.out.push(b' ');
+        }
+        self.out.extend_from_slice(text);
+        self.line_start = false;
+        self.pending_space = false;
+        self.before_prev = if separate { None } else { self.prev };
+        self.prev = Some((token.span, text));
+      }
+    }
+  }
+
+  /// Ends the last line, so the next file starts on a fresh one.
+  pub fn finish(mut self) {
+    if !self.line_start || self.pending_newlines > 0 {
+      self.newline();
+    }
+  }
+
+  fn newline(&mut self) {
+    self.out.push(b'\n');
+    for _ in 0..std::mem::take(&mut self.pending_newlines) {
+      self.out.push(b'\n');
+    }
+    self.line_start = true;
+    self.pending_space = false;
+    self.prev = None;
+    self.before_prev = None;
+  }
+
+  /// Whether `pieces` written end to end would lex as something other than
+  /// one token per piece.
+  fn pastes(&self, pieces: &[&[u8]]) -> bool {
+    let joined = pieces.concat();
+    let mut lexer = Lexer::with_options(&joined, FileId::DUMMY, self.lex_options);
+    pieces
+      .iter()
+      .any(|piece| lexer.next_token().span.len() as usize != piece.len())
+  }
+}
+
+/// Whether `second` starts right where `first` ends in the same file.
+fn adjacent(first: Span, second: Span) -> bool {
+  !first.is_dummy() && first.file() == second.file() && first.end() == second.start()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::Emitter;
+  use slopcc_common::prelude::{
+    FileId,
+    Span,
+  };
+  use slopcc_lex::{
+    LexOptions,
+    Lexer,
+    Token,
+    TokenKind,
+  };
+
+  fn emit(src: &[u8], preserve_comments: bool) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut emitter = Emitter::new(&mut out, preserve_comments, LexOptions::default());
+    for token in Lexer::new(src, FileId::new_for_tests(0)) {
+      emitter.token(token, token.span.as_bytes(src));
+    }
+    emitter.finish();
+    out
+  }
+
+  fn significant_kinds(src: &[u8]) -> Vec<TokenKind> {
+    Lexer::new(src, FileId::new_for_tests(0))
+      .filter(|token| !token.kind.is_trivia() && !token.kind.is_eof())
+      .map(|token| token.kind)
+      .collect()
+  }
+
+  #[test]
+  fn drops_comments_and_collapses_interior_whitespace() {
+    assert_eq!(emit(b"int /* c */ x;   // tail\n", false), b"int x;\n");
+    assert_eq!(emit(b"  indented\tstays\n\nnext", false), b"  indented stays\n\nnext\n");
+  }
+
+  #[test]
+  fn keeps_comments_when_requested() {
+    let src = b"a /* c */ b // d\n";
+    assert_eq!(emit(src, true), src.to_vec());
+  }
+
+  #[test]
+  fn multi_line_comment_keeps_later_line_numbers() {
+    assert_eq!(emit(b"a /* 1\n2 */ b\nc\n", false), b"a b\n\nc\n");
+  }
+
+  #[test]
+  fn separates_tokens_that_would_paste() {
+    let pieces: [&[u8]; 8] = [b"+", b"+", b"a", b"1", b".", b"5", b"-", b">"];
+    let mut out = Vec::new();
+    let mut emitter = Emitter::new(&mut out, false, LexOptions::default());
+    for piece in pieces {
+      let token = Lexer::new(piece, FileId::new_for_tests(0)).next_token();
+      emitter.token(token, piece);
+    }
+    emitter.finish();
+    assert_eq!(out, b"+ +a 1 . 5- >\n");
+    assert_eq!(significant_kinds(&out).len(), 8);
+  }
+
+  /// Emits each piece as a token with nothing between them and returns the
+  /// output's significant spellings.
+  fn emit_adjacent(pieces: &[&[u8]]) -> Vec<Vec<u8>> {
+    let mut out = Vec::new();
+    let mut emitter = Emitter::new(&mut out, false, LexOptions::default());
+    for &piece in pieces {
+      let token = Lexer::new(piece, FileId::new_for_tests(0)).next_token();
+      assert_eq!(token.span.len() as usize, piece.len(), "{piece:?} is not one token");
+      emitter.token(token, piece);
+    }
+    emitter.finish();
+    Lexer::new(&out, FileId::new_for_tests(0))
+      .filter(|token| !token.kind.is_trivia() && !token.kind.is_eof())
+      .map(|token| token.span.as_bytes(&out).to_vec())
+      .collect()
+  }
+
+  #[test]
+  fn tricky_adjacencies_round_trip() {
+    let corpus: [&[&[u8]]; 12] = [
+      &[b"-", b"-"],
+      &[b"/", b"/"],
+      &[b"/", b"*"],
+      &[b".", b"."],
+      &[b".", b".", b"."],
+      &[b".", b".", b".", b"."],
+      &[b"<", b"<"],
+      &[b"<<", b"="],
+      &[b"#", b"#"],
+      &[b"&", b"&="],
+      &[b"-", b">"],
+      &[b"L", b"'a'"],
+    ];
+    for pieces in corpus {
+      assert_eq!(emit_adjacent(pieces), pieces, "{pieces:?}");
+    }
+  }
+
+  #[test]
+  fn tokens_adjacent_in_the_source_are_not_relexed() {
+    let file = FileId::new_for_tests(0);
+    let mut out = Vec::new();
+    let mut emitter = Emitter::new(&mut out, false, LexOptions::default());
+    emitter.token(Token::new(TokenKind::Plus, Span::new(file, 0, 1)), b"+");
+    emitter.token(Token::new(TokenKind::Plus, Span::new(file, 1, 2)), b"+");
+    emitter.token(Token::new(TokenKind::Plus, Span::new(file, 5, 6)), b"+");
+    emitter.finish();
+    assert_eq!(out, b"++ +\n");
+  }
+
+  #[test]
+  fn paste_checks_use_the_lexer_options() {
+    let emit_with = |digit_separators| {
+      let options = LexOptions {
+        digit_separators,
+        ..LexOptions::default()
+      };
+      let mut out = Vec::new();
+      let mut emitter = Emitter::new(&mut out, false, options);
+      for piece in [&b"1"[..], b"'a'"] {
+        let token = Lexer::with_options(piece, FileId::new_for_tests(0), options).next_token();
+        emitter.token(token, piece);
+      }
+      emitter.finish();
+      out
+    };
+    assert_eq!(emit_with(true), b"1 'a'\n");
+    assert_eq!(emit_with(false), b"1'a'\n");
+  }
+
+  #[test]
+  fn relexing_output_gives_the_same_tokens() {
+    let src: &[u8] = b"x=a+++b/**/-1.e+5/*\n*/;L'c' u8\"s\"//z\n#define F(a) a##1\n";
+    let out = emit(src, false);
+    assert_eq!(significant_kinds(&out), significant_kinds(src));
+  }
+}
@@ -0,0 +1,214 @@
+use std::path::{
+  Path,
+  PathBuf,
+};
+
+use crate::cli::CliOptions;
+
+/// Directories searched for `<...>` includes after the `-I` and `-isystem`
+/// directories.
+const BUILTIN_SYSTEM_DIRS: &[&str] = &["/usr/local/include", "/usr/include"];
+
+/// A header found by [`IncludeResolver::resolve`].
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct ResolvedInclude {
+  pub path: PathBuf,
+  /// Found in a system directory (`-isystem` or built-in).
+  pub system: bool,
+}
+
+/// Ordered include search path. The first directory holding the header wins.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct IncludeResolver {
+  /// `-iquote` dirs in command-line order; quoted includes only.
+  quote_dirs: Vec<PathBuf>,
+  /// `-I` dirs in command-line order.
+  user_dirs: Vec<PathBuf>,
+  /// `-isystem` dirs in command-line order.
+  system_dirs: Vec<PathBuf>,
+  /// Built-in system dirs, searched last; empty under `-nostdinc`.
+  builtin_dirs: Vec<PathBuf>,
+}
+
+impl IncludeResolver {
+  #[must_use]
+  pub fn new(include_dirs: &[PathBuf], system_include_dirs: &[PathBuf]) -> Self {
+    Self {
+      quote_dirs: Vec::new(),
+      user_dirs: include_dirs.to_vec(),
+      system_dirs: system_include_dirs.to_vec(),
+      builtin_dirs: BUILTIN_SYSTEM_DIRS.iter().map(PathBuf::from).collect(),
+    }
+  }
+
+  #[must_use]
+  pub fn from_options(options: &CliOptions) -> Self {
+    let resolver = Self::new(&options.include_dirs, &options.system_include_dirs)
+      .with_quote_dirs(&options.quote_include_dirs);
+    if options.nostdinc {
+      resolver.without_builtin_dirs()
+    } else {
+      resolver
+    }
+  }
+
+  /// Adds `-iquote` dirs, searched after the includer's directory and
+  /// before `-I` for quoted includes only.
+  #[must_use]
+  pub fn with_quote_dirs(mut self, quote_dirs: &[PathBuf]) -> Self {
+    self.quote_dirs.extend_from_slice(quote_dirs);
+    self
+  }
+
+  /// Drops the built-in system dirs (`-nostdinc`).
+  #[must_use]
+  pub fn without_builtin_dirs(mut self) -> Self {
+    self.builtin_dirs.clear();
+    self
+  }
+
+  /// Finds `name`. Quoted includes (`angled == false`) try `current_dir`,
+  /// the including file's directory, and then the `-iquote` dirs; after
+  /// that both forms search the `-I` dirs, the `-isystem` dirs and the
+  /// built-in dirs.
+  #[must_use]
+  pub fn resolve(
+    &self,
+    name: &str,
+    angled: bool,
+    current_dir: Option<&Path>,
+  ) -> Option<ResolvedInclude> {
+    let quote_dirs: &[PathBuf] = if angled { &[] } else { &self.quote_dirs };
+    let quoted = current_dir
+      .filter(|_| !angled)
+      .into_iter()
+      .chain(quote_dirs.iter().map(PathBuf::as_path))
+      .map(|dir| (dir, false));
+    let user = self.user_dirs.iter().map(|dir| (dir.as_path(), false));
+    let system = self
+      .system_dirs
+      .iter()
+      .chain(&self.builtin_dirs)
+      .map(|dir| (dir.as_path(), true));
+
+    quoted
+      .chain(user)
+      .chain(system)
+      .map(|(dir, system)| ResolvedInclude {
+        path: dir.join(name),
+        system,
+      })
+      .find(|found| found.path.is_file())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::{
+    fs,
+    path::PathBuf,
+  };
+
+  use super::{
+    IncludeResolver,
+    ResolvedInclude,
+  };
+
+  /// Scratch tree removed on drop.
+  struct Tree(PathBuf);
+
+  impl Tree {
+    fn new(name: &str, files: &[&str]) -> Self {
+      let root = std::env::temp_dir().join(format!("slopcc-include-{name}-{}", std::process::id()));
+      for file in files {
+        let path = root.join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, "").unwrap();
+      }
+      Self(root)
+    }
+
+    fn dir(&self, name: &str) -> PathBuf {
+      self.0.join(name)
+    }
+  }
+
+  impl Drop for Tree {
+    fn drop(&mut self) {
+      let _ = fs::remove_dir_all(&self.0);
+    }
+  }
+
+  fn found(path: PathBuf, system: bool) -> Option<ResolvedInclude> {
+    Some(ResolvedInclude { path, system })
+  }
+
+  #[test]
+  fn first_matching_dir_wins() {
+    let tree = Tree::new("first", &["a/h.h", "b/h.h", "b/only_b.h"]);
+    let resolver = IncludeResolver::new(&[tree.dir("a"), tree.dir("b")], &[]);
+    assert_eq!(resolver.resolve("h.h", true, None), found(tree.dir("a").join("h.h"), false));
+    assert_eq!(
+      resolver.resolve("only_b.h", true, None),
+      found(tree.dir("b").join("only_b.h"), false)
+    );
+    assert_eq!(resolver.resolve("missing-slopcc-header.h", true, None), None);
+  }
+
+  #[test]
+  fn quoted_includes_search_current_dir_first() {
+    let tree = Tree::new("quoted", &["src/h.h", "inc/h.h"]);
+    let resolver = IncludeResolver::new(&[tree.dir("inc")], &[]);
+    let current = tree.dir("src");
+    assert_eq!(
+      resolver.resolve("h.h", false, Some(&current)),
+      found(current.join("h.h"), false)
+    );
+    assert_eq!(
+      resolver.resolve("h.h", true, Some(&current)),
+      found(tree.dir("inc").join("h.h"), false)
+    );
+  }
+
+  #[test]
+  fn iquote_dirs_serve_only_quoted_includes() {
+    let tree = Tree::new("iquote", &["quote/h.h", "quote/both.h", "inc/both.h"]);
+    let resolver =
+      IncludeResolver::new(&[tree.dir("inc")], &[]).with_quote_dirs(&[tree.dir("quote")]);
+    assert_eq!(resolver.resolve("h.h", false, None), found(tree.dir("quote").join("h.h"), false));
+    assert_eq!(resolver.resolve("h.h", true, None), None);
+    assert_eq!(
+      resolver.resolve("both.h", false, None),
+      found(tree.dir("quote").join("both.h"), false)
+    );
+    assert_eq!(
+      resolver.resolve("both.h", true, None),
+      found(tree.dir("inc").join("both.h"), false)
+    );
+  }
+
+  #[test]
+  fn searches_user_dirs_before_isystem_dirs() {
+    let tree = Tree::new("system", &["user/both.h", "sys/both.h", "sys/sys.h"]);
+    let resolver = IncludeResolver::new(&[tree.dir("user")], &[tree.dir("sys")]);
+    assert_eq!(
+      resolver.resolve("both.h", true, None),
+      found(tree.dir("user").join("both.h"), false)
+    );
+    assert_eq!(resolver.resolve("sys.h", false, None), found(tree.dir("sys").join("sys.h"), true));
+  }
+
+  #[test]
+  fn without_builtin_dirs_searches_only_explicit_dirs() {
+    let tree = Tree::new("nostdinc", &["default/std.h", "inc/mine.h"]);
+    let mut resolver = IncludeResolver::new(&[tree.dir("inc")], &[]);
+    resolver.builtin_dirs = vec![tree.dir("default")];
+    let default_header = found(tree.dir("default").join("std.h"), true);
+    assert_eq!(resolver.resolve("std.h", true, None), default_header);
+
+    let resolver = resolver.without_builtin_dirs();
+    assert_eq!(resolver.resolve("std.h", true, None), None);
+    let user_header = found(tree.dir("inc").join("mine.h"), false);
+    assert_eq!(resolver.resolve("mine.h", true, None), user_header);
+  }
+}
@@ -0,0 +1,13 @@
+//! The slopcc compiler driver as a library, for tools that embed it instead
+//! of running the CLI. `main.rs` is a thin wrapper over [`cli`] and
+//! [`driver`].
+
+pub mod cli;
+mod deps;
+pub mod driver;
+mod emit;
+mod include;
+mod macros;
+mod warnings;
+
+pub use driver::preprocess_file;
@@ -0,0 +1,101 @@
+use std::collections::BTreeMap;
+
+use crate::cli::{
+  CliOptions,
+  OptLevel,
+  StdVersion,
+};
+
+/// Macros the compiler predefines for `options`, before any `-D`/`-U`.
+#[must_use]
+pub fn builtin_macros(options: &CliOptions) -> Vec<(&'static str, &'static str)> {
+  let mut macros = vec![("__STDC__", "1"), ("__STDC_HOSTED__", "1"), ("__slopcc__", "1")];
+  if let Some(version) = stdc_version(options.std) {
+    macros.push(("__STDC_VERSION__", version));
+  }
+  if !options.std.is_gnu() {
+    macros.push(("__STRICT_ANSI__", "1"));
+  }
+  match options.opt {
+    None | Some(OptLevel::O0) => {}
+    Some(level) => {
+      macros.push(("__OPTIMIZE__", "1"));
+      if matches!(level, OptLevel::Os | OptLevel::Oz) {
+        macros.push(("__OPTIMIZE_SIZE__", "1"));
+      }
+    }
+  }
+  macros
+}
+
+/// `__STDC_VERSION__` for `std`; C89 predates it.
+fn stdc_version(std: StdVersion) -> Option<&'static str> {
+  match std {
+    StdVersion::C89 | StdVersion::Gnu89 => None,
+    StdVersion::C99 | StdVersion::Gnu99 => Some("199901L"),
+    StdVersion::C11 | StdVersion::Gnu11 => Some("201112L"),
+    StdVersion::C17 | StdVersion::Gnu17 => Some("201710L"),
+    StdVersion::C23 | StdVersion::Gnu23 => Some("202311L"),
+  }
+}
+
+/// `-dM` output: one `#define` per macro in effect, sorted by name. The
+/// built-ins come first and `-D`/`-U` apply over them in command-line order.
+/// Macros defined in the sources join once the preprocessor exists.
+#[must_use]
+pub fn dump_macros(options: &CliOptions) -> String {
+  let mut defined: BTreeMap<&str, (&str, &str)> = BTreeMap::new();
+  for (name, value) in builtin_macros(options) {
+    defined.insert(name, (name, value));
+  }
+  for def in &options.macros {
+    let key = def.name.split_once('(').map_or(def.name.as_str(), |(name, _)| name);
+    match &def.value {
+      Some(value) => defined.insert(key, (&def.name, value)),
+      None => defined.remove(key),
+    };
+  }
+
+  let mut out = String::new();
+  for (name, value) in defined.values() {
+    out.push_str(&format!("#define {name} {value}\n"));
+  }
+  out
+}
+
+#[cfg(test)]
+mod tests {
+  use super::dump_macros;
+  use crate::cli::parse_args;
+
+  fn dump(args: &[&str]) -> String {
+    let opts = parse_args(args.iter().map(std::ffi::OsString::from))
+      .expect("test arguments should parse");
+    dump_macros(&opts)
+  }
+
+  #[test]
+  fn dumps_command_line_macros_sorted_over_builtins() {
+    let out = dump(&["slopcc", "-E", "-dM", "-DZED=2", "-DFOO", "-DF(x)=x", "a.c"]);
+    let lines: Vec<_> = out.lines().collect();
+    assert!(lines.contains(&"#define FOO 1"));
+    assert!(lines.contains(&"#define F(x) x"));
+    assert!(lines.contains(&"#define __STDC_VERSION__ 201710L"));
+    let mut sorted = lines.clone();
+    sorted.sort_unstable();
+    assert_eq!(lines, sorted);
+  }
+
+  #[test]
+  fn later_undefine_and_std_change_the_dump() {
+    let out = dump(&["slopcc", "-E", "-dM", "-DFOO", "-UFOO", "-U__STDC__", "-std=gnu89", "a.c"]);
+    assert!(!out.contains("FOO"));
+    assert!(!out.contains("#define __STDC__ "));
+    assert!(!out.contains("__STDC_VERSION__"));
+    assert!(!out.contains("__STRICT_ANSI__"));
+
+    let out = dump(&["slopcc", "-E", "-dM", "-Os", "a.c"]);
+    assert!(out.contains("#define __OPTIMIZE__ 1\n"));
+    assert!(out.contains("#define __OPTIMIZE_SIZE__ 1\n"));
+  }
+}
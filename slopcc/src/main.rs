@@ -1,8 +1,10 @@
-mod cli;
-mod driver;
-
 use std::process::ExitCode;
 
+use slopcc::{
+  cli,
+  driver,
+};
+
 #[global_allocator]
 static GLOBAL: mimalloc::MiMalloc = mimalloc::MiMalloc;
 
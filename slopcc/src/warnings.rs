@@ -0,0 +1,220 @@
+use slopcc_common::prelude::{
+  Diagnostic,
+  Diagnostics,
+  Severity,
+};
+
+/// Which warnings are reported, built from `-w` and the `-W` flags in
+/// command-line order.
+#[derive(Default, Clone, Eq, PartialEq, Debug)]
+pub struct WarningConfig {
+  /// `-w`: drop every warning regardless of the other flags, as GCC does.
+  pub suppress_all: bool,
+  /// `-W<name>`/`-Wno-<name>` settings in command-line order; `name` is a
+  /// warning code or a group such as `all`.
+  settings: Vec<(String, bool)>,
+  /// `-Werror`: promote every reported warning to an error.
+  pub all_errors: bool,
+  /// `-Werror=<name>`/`-Wno-error=<name>` settings in command-line order.
+  error_settings: Vec<(String, bool)>,
+}
+
+impl WarningConfig {
+  /// Applies one `-W` flag value (the text after `-W`). `-Werror=<name>`
+  /// also enables the warning, as in GCC.
+  pub fn apply_flag(&mut self, flag: &str) {
+    let (name, enabled) = match flag.strip_prefix("no-") {
+      Some(name) => (name, false),
+      None => (flag, true),
+    };
+    if name == "error" {
+      self.all_errors = enabled;
+    } else if let Some(code) = name.strip_prefix("error=") {
+      self.error_settings.push((code.to_owned(), enabled));
+      if enabled {
+        self.settings.push((code.to_owned(), true));
+      }
+    } else {
+      self.settings.push((name.to_owned(), enabled));
+    }
+  }
+
+  /// Whether a warning with `code` is reported. The last flag naming the
+  /// code or its group wins; otherwise only warnings outside every group,
+  /// other than `pedantic`, are on. Uncoded warnings are on unless `-w` is
+  /// given.
+  #[must_use]
+  pub fn is_enabled(&self, code: Option<&str>) -> bool {
+    if self.suppress_all {
+      return false;
+    }
+    let Some(code) = code else {
+      return true;
+    };
+    last_setting(&self.settings, code).unwrap_or(group(code).is_none() && code != "pedantic")
+  }
+
+  /// Whether a reported warning with `code` becomes an error: the last
+  /// `-W[no-]error=` naming it wins, otherwise `-Werror` decides.
+  #[must_use]
+  pub fn is_error(&self, code: Option<&str>) -> bool {
+    code
+      .and_then(|code| last_setting(&self.error_settings, code))
+      .unwrap_or(self.all_errors)
+  }
+
+  /// Drops disabled warnings and promotes the `-Werror` ones; errors and
+  /// notes always pass through.
+  #[must_use]
+  pub fn filter(&self, diagnostics: &Diagnostics) -> Diagnostics {
+    let mut kept: Diagnostics = diagnostics
+      .iter()
+      .filter(|diagnostic| self.keeps(diagnostic))
+      .cloned()
+      .collect();
+    kept.promote_warnings_to_errors(|diagnostic| self.is_error(diagnostic.code));
+    kept
+  }
+
+  fn keeps(&self, diagnostic: &Diagnostic) -> bool {
+    diagnostic.severity != Severity::Warning || self.is_enabled(diagnostic.code)
+  }
+}
+
+/// Whether `flag` (the text after `-W`) names `error`, a known warning code
+/// or a group, with or without `no-` and `error=`.
+#[must_use]
+pub fn is_known_flag(flag: &str) -> bool {
+  let name = flag.strip_prefix("no-").unwrap_or(flag);
+  let name = name.strip_prefix("error=").unwrap_or(name);
+  name == "error" || CODES.contains(&name) || GROUPS.contains(&name)
+}
+
+/// Codes the compiler tags its warnings with.
+const CODES: &[&str] = &["comment", "pedantic"];
+
+/// Group names `-W` accepts.
+const GROUPS: &[&str] = &["all", "extra"];
+
+/// Value of the last setting naming `code` or its group.
+fn last_setting(settings: &[(String, bool)], code: &str) -> Option<bool> {
+  let group = group(code);
+  settings
+    .iter()
+    .rev()
+    .find(|(name, _)| name == code || Some(name.as_str()) == group)
+    .map(|&(_, enabled)| enabled)
+}
+
+/// The group flag (`-Wall`, `-Wextra`) that turns a warning on. Warnings
+/// outside every group are on by default.
+fn group(code: &str) -> Option<&'static str> {
+  match code {
+    "comment" => Some("all"),
+    _ => None,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{
+    is_known_flag,
+    WarningConfig,
+  };
+  use slopcc_common::prelude::{
+    Diagnostic,
+    Diagnostics,
+    FileId,
+    Severity,
+    Span,
+  };
+
+  fn config(flags: &[&str]) -> WarningConfig {
+    let mut config = WarningConfig::default();
+    for flag in flags {
+      config.apply_flag(flag);
+    }
+    config
+  }
+
+  fn diagnostics() -> Diagnostics {
+    let span = Span::new(FileId::new_for_tests(0), 0, 1);
+    let mut diagnostics = Diagnostics::new();
+    diagnostics.push(Diagnostic::warning("foo", span).with_code("foo"));
+    diagnostics.push(Diagnostic::warning("bar", span).with_code("bar"));
+    diagnostics.push(Diagnostic::error("broken", span));
+    diagnostics
+  }
+
+  #[test]
+  fn suppress_all_keeps_only_errors() {
+    let mut config = config(&["all", "foo"]);
+    config.suppress_all = true;
+    let kept = config.filter(&diagnostics());
+    let messages: Vec<_> = kept.iter().map(|d| d.message.as_str()).collect();
+    assert_eq!(messages, ["broken"]);
+  }
+
+  #[test]
+  fn no_flag_disables_only_that_warning() {
+    let kept = config(&["no-foo"]).filter(&diagnostics());
+    let messages: Vec<_> = kept.iter().map(|d| d.message.as_str()).collect();
+    assert_eq!(messages, ["bar", "broken"]);
+  }
+
+  #[test]
+  fn later_flags_override_earlier_ones() {
+    assert!(config(&["no-foo", "foo"]).is_enabled(Some("foo")));
+    assert!(!config(&["foo", "no-foo"]).is_enabled(Some("foo")));
+    assert!(config(&["no-comment", "all"]).is_enabled(Some("comment")));
+    assert!(!config(&["all", "no-comment"]).is_enabled(Some("comment")));
+  }
+
+  #[test]
+  fn grouped_warnings_need_their_group() {
+    assert!(!config(&[]).is_enabled(Some("comment")));
+    assert!(config(&["all"]).is_enabled(Some("comment")));
+    assert!(config(&["comment"]).is_enabled(Some("comment")));
+    assert!(config(&[]).is_enabled(None));
+  }
+
+  #[test]
+  fn werror_promotes_reported_warnings() {
+    let kept = config(&["error", "no-foo"]).filter(&diagnostics());
+    let severities: Vec<_> = kept.iter().map(|d| d.severity).collect();
+    assert_eq!(severities, [Severity::Error, Severity::Error]);
+  }
+
+  #[test]
+  fn error_equals_selects_single_warning() {
+    let config = config(&["error=comment"]);
+    assert!(config.is_enabled(Some("comment")));
+    assert!(config.is_error(Some("comment")));
+    assert!(!config.is_error(Some("foo")));
+  }
+
+  #[test]
+  fn no_error_equals_exempts_warning_from_werror() {
+    let config = config(&["error", "no-error=foo"]);
+    assert!(!config.is_error(Some("foo")));
+    assert!(config.is_error(Some("bar")));
+    assert!(config.is_error(None));
+  }
+
+  #[test]
+  fn knows_codes_groups_and_error_forms() {
+    for flag in ["all", "extra", "comment", "no-comment", "error", "no-error", "error=comment"] {
+      assert!(is_known_flag(flag), "-W{flag} should be known");
+    }
+    assert!(!is_known_flag("foo"));
+    assert!(!is_known_flag("error=foo"));
+  }
+
+  #[test]
+  fn pedantic_is_off_until_requested() {
+    assert!(!config(&[]).is_enabled(Some("pedantic")));
+    assert!(!config(&["all", "extra"]).is_enabled(Some("pedantic")));
+    assert!(config(&["pedantic"]).is_enabled(Some("pedantic")));
+    assert!(config(&["error=pedantic"]).is_error(Some("pedantic")));
+  }
+}
@@ -0,0 +1,53 @@
+// Each test binary compiles its own copy and uses only some helpers.
+#![allow(dead_code)]
+
+use std::{
+  fs,
+  path::{
+    Path,
+    PathBuf,
+  },
+  process::Command,
+  sync::atomic::{
+    AtomicU32,
+    Ordering,
+  },
+};
+
+/// Scratch directory removed when dropped.
+pub struct TempDir {
+  path: PathBuf,
+}
+
+impl TempDir {
+  pub fn new() -> Self {
+    static NEXT: AtomicU32 = AtomicU32::new(0);
+    let path = std::env::temp_dir().join(format!(
+      "slopcc-test-{}-{}",
+      std::process::id(),
+      NEXT.fetch_add(1, Ordering::Relaxed)
+    ));
+    fs::create_dir_all(&path).expect("temp dir should be creatable");
+    Self { path }
+  }
+
+  pub fn path(&self) -> &Path {
+    &self.path
+  }
+
+  pub fn write(&self, name: &str, contents: &[u8]) -> PathBuf {
+    let path = self.path.join(name);
+    fs::write(&path, contents).expect("temp file should be writable");
+    path
+  }
+}
+
+impl Drop for TempDir {
+  fn drop(&mut self) {
+    let _ = fs::remove_dir_all(&self.path);
+  }
+}
+
+pub fn slopcc() -> Command {
+  Command::new(env!("CARGO_BIN_EXE_slopcc"))
+}
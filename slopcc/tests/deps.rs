@@ -0,0 +1,90 @@
+mod common;
+
+use common::{
+  slopcc,
+  TempDir,
+};
+
+#[test]
+fn dash_m_prints_rule_for_source_and_local_header() {
+  let dir = TempDir::new();
+  dir.write("header.h", b"int helper(void);\n");
+  let input = dir.write("foo.c", b"#include \"header.h\"\n#include <stdio.h>\n");
+
+  let output = slopcc()
+    .arg("-MM")
+    .arg(&input)
+    .output()
+    .expect("slopcc should run");
+
+  assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+  let rule = String::from_utf8(output.stdout).expect("rule should be UTF-8");
+  let expected = format!(
+    "foo.o: {} {}\n",
+    input.display(),
+    dir.path().join("header.h").display()
+  );
+  assert_eq!(rule, expected);
+}
+
+#[test]
+fn dash_mf_writes_rule_to_file() {
+  let dir = TempDir::new();
+  let input = dir.write("foo.c", b"int x;\n");
+  let dep_file = dir.path().join("deps.d");
+
+  let output = slopcc()
+    .arg("-M")
+    .arg("-MF")
+    .arg(&dep_file)
+    .arg(&input)
+    .output()
+    .expect("slopcc should run");
+
+  assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+  assert!(output.stdout.is_empty());
+  let rule = std::fs::read_to_string(&dep_file).expect("-MF file should exist");
+  assert_eq!(rule, format!("foo.o: {}\n", input.display()));
+}
+
+#[test]
+fn dash_mt_overrides_rule_target() {
+  let dir = TempDir::new();
+  let input = dir.write("foo.c", b"int x;\n");
+
+  let output = slopcc()
+    .args(["-M", "-MT", "build/foo.o"])
+    .arg(&input)
+    .output()
+    .expect("slopcc should run");
+
+  assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+  let rule = String::from_utf8(output.stdout).expect("rule should be UTF-8");
+  assert_eq!(rule, format!("build/foo.o: {}\n", input.display()));
+}
+
+#[test]
+fn dash_mmd_writes_dep_file_alongside_output() {
+  let dir = TempDir::new();
+  dir.write("header.h", b"int helper(void);\n");
+  let input = dir.write("foo.c", b"#include \"header.h\"\n#include <stdio.h>\nint x;\n");
+  let out = dir.path().join("foo.i");
+
+  let output = slopcc()
+    .args(["-E", "-MMD", "-o"])
+    .arg(&out)
+    .arg(&input)
+    .output()
+    .expect("slopcc should run");
+
+  assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+  assert!(out.is_file(), "normal output should still be written");
+  let rule = std::fs::read_to_string(dir.path().join("foo.d")).expect(".d file should exist");
+  let expected = format!(
+    "{}: {} {}\n",
+    out.display(),
+    input.display(),
+    dir.path().join("header.h").display()
+  );
+  assert_eq!(rule, expected);
+}
@@ -0,0 +1,63 @@
+mod common;
+
+use common::slopcc;
+
+#[test]
+fn help_lists_supported_flags() {
+  let output = slopcc().arg("--help").output().expect("slopcc should run");
+
+  assert!(output.status.success());
+  let usage = String::from_utf8(output.stdout).expect("usage should be UTF-8");
+  assert!(usage.starts_with("Usage: slopcc"));
+  for flag in [
+    "-E", "-S", "-c", "-o", "-fsyntax-only", "-save-temps", "-dM", "-x", "-I", "-iquote",
+    "-isystem", "-nostdinc", "-include", "-D", "-U", "-l", "-L", "-C", "-P", "-M", "-MM", "-MD",
+    "-MMD", "-MF", "-MT", "-MQ", "-w", "--color", "-W", "-Werror", "-pedantic", "-pedantic-errors",
+    "-std=", "-O", "-g", "-pipe", "-fPIC", "-v", "-###", "--version", "-dumpmachine",
+    "-print-file-name=", "--help",
+  ] {
+    assert!(usage.contains(flag), "usage should mention {flag}");
+  }
+}
+
+#[test]
+fn short_help_matches_long_help() {
+  let long = slopcc().arg("--help").output().expect("slopcc should run");
+  let short = slopcc().arg("-h").output().expect("slopcc should run");
+  assert!(short.status.success());
+  assert_eq!(short.stdout, long.stdout);
+}
+
+#[test]
+fn dumpmachine_prints_a_triple() {
+  let output = slopcc().arg("-dumpmachine").output().expect("slopcc should run");
+
+  assert!(output.status.success());
+  let triple = String::from_utf8(output.stdout).expect("triple should be UTF-8");
+  assert!(triple.ends_with('\n'));
+  assert!(triple.trim().split('-').count() >= 2, "triple: {triple}");
+
+  let version = slopcc().arg("--version").output().expect("slopcc should run");
+  assert!(version.status.success());
+  assert!(version.stdout.starts_with(b"slopcc "));
+}
+
+#[test]
+fn print_file_name_searches_library_dirs() {
+  let dir = common::TempDir::new();
+  let lib = dir.write("libfoo.a", b"");
+
+  let output = slopcc()
+    .arg("-L")
+    .arg(dir.path())
+    .arg("-print-file-name=libfoo.a")
+    .output()
+    .expect("slopcc should run");
+  assert!(output.status.success());
+  assert_eq!(String::from_utf8_lossy(&output.stdout), format!("{}\n", lib.display()));
+
+  let output =
+    slopcc().arg("-print-file-name=libnone.a").output().expect("slopcc should run");
+  assert!(output.status.success());
+  assert_eq!(output.stdout, b"libnone.a\n");
+}
@@ -0,0 +1,37 @@
+mod common;
+
+use std::ffi::OsString;
+
+use common::TempDir;
+use slopcc::{
+  cli::parse_args,
+  preprocess_file,
+};
+use slopcc_lex::TokenKind;
+
+#[test]
+fn preprocess_file_returns_tokens_without_printing() {
+  let dir = TempDir::new();
+  let input = dir.write("tiny.c", b"int x;\n");
+  let args = [OsString::from("slopcc"), OsString::from("-E"), input.clone().into_os_string()];
+  let options = parse_args(args).expect("arguments should parse");
+
+  let (sources, tokens, diagnostics) =
+    preprocess_file(&input, &options).expect("tiny.c should preprocess");
+
+  let kinds: Vec<_> = tokens.iter().map(|token| token.kind).collect();
+  assert_eq!(
+    kinds,
+    [
+      TokenKind::Ident,
+      TokenKind::Whitespace,
+      TokenKind::Ident,
+      TokenKind::Semi,
+      TokenKind::Newline,
+      TokenKind::Eof,
+    ]
+  );
+  assert!(diagnostics.is_empty());
+  let file = sources.file(tokens[0].span.file());
+  assert_eq!(tokens[2].span.as_bytes(file.bytes()), b"x");
+}
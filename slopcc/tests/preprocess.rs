@@ -0,0 +1,359 @@
+mod common;
+
+use common::{
+  slopcc,
+  TempDir,
+};
+
+#[test]
+fn preprocess_only_prints_source_without_comments() {
+  let dir = TempDir::new();
+  let input = dir.write("tiny.c", b"int main(void) { /* body */ return 0; } // end\n");
+
+  let output = slopcc()
+    .args(["-E", "-P"])
+    .arg(&input)
+    .output()
+    .expect("slopcc should run");
+
+  assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+  assert_eq!(output.stdout, b"int main(void) { return 0; }\n");
+}
+
+#[test]
+fn preprocess_only_writes_to_output_file() {
+  let dir = TempDir::new();
+  let input = dir.write("tiny.c", b"#define X 1\nX\n");
+  let out_path = dir.path().join("tiny.i");
+
+  let output = slopcc()
+    .args(["-E", "-P"])
+    .arg(&input)
+    .arg("-o")
+    .arg(&out_path)
+    .output()
+    .expect("slopcc should run");
+
+  assert!(output.status.success());
+  assert!(output.stdout.is_empty());
+  assert_eq!(std::fs::read(&out_path).unwrap(), b"#define X 1\nX\n");
+}
+
+#[test]
+fn line_markers_name_each_file_unless_dash_p() {
+  let dir = TempDir::new();
+  dir.write("config.h", b"#define A 1\n");
+  dir.write("foo.c", b"int a;\n\nint b;\n");
+
+  let output = slopcc()
+    .current_dir(dir.path())
+    .args(["-E", "-include", "config.h", "foo.c"])
+    .output()
+    .expect("slopcc should run");
+  assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+  assert_eq!(
+    output.stdout,
+    b"# 1 \"config.h\"\n#define A 1\n# 1 \"foo.c\"\nint a;\n\nint b;\n"
+  );
+
+  let output = slopcc()
+    .current_dir(dir.path())
+    .args(["-E", "-P", "-include", "config.h", "foo.c"])
+    .output()
+    .expect("slopcc should run");
+  assert!(output.status.success());
+  assert_eq!(output.stdout, b"#define A 1\nint a;\n\nint b;\n");
+}
+
+#[test]
+fn wall_reports_nested_comment_and_w_silences_it() {
+  let dir = TempDir::new();
+  let input = dir.write("foo.c", b"/* a /* b */ int x;\n");
+
+  let output = slopcc().args(["-E", "-Wall"]).arg(&input).output().expect("slopcc should run");
+  assert!(output.status.success());
+  let stderr = String::from_utf8_lossy(&output.stderr);
+  assert!(stderr.contains("warning: \"/*\" within comment"), "stderr: {stderr}");
+
+  let output =
+    slopcc().args(["-E", "-Wall", "-w"]).arg(&input).output().expect("slopcc should run");
+  assert!(output.status.success());
+  assert!(output.stderr.is_empty());
+
+  let output = slopcc().arg("-E").arg(&input).output().expect("slopcc should run");
+  assert!(output.stderr.is_empty(), "-Wcomment is only in -Wall");
+}
+
+#[test]
+fn werror_turns_warning_into_failure() {
+  let dir = TempDir::new();
+  let input = dir.write("foo.c", b"/* a /* b */ int x;\n");
+
+  let output = slopcc().args(["-E", "-Wall"]).arg(&input).output().expect("slopcc should run");
+  assert!(output.status.success());
+
+  let output =
+    slopcc().args(["-E", "-Wall", "-Werror"]).arg(&input).output().expect("slopcc should run");
+  assert!(!output.status.success());
+  let stderr = String::from_utf8_lossy(&output.stderr);
+  assert!(stderr.contains("error: \"/*\" within comment"), "stderr: {stderr}");
+
+  let output = slopcc()
+    .args(["-E", "-Wall", "-Werror", "-Wno-error=comment"])
+    .arg(&input)
+    .output()
+    .expect("slopcc should run");
+  assert!(output.status.success());
+}
+
+#[test]
+fn dash_x_c_preprocesses_unknown_extension() {
+  let dir = TempDir::new();
+  let input = dir.write("foo.unknown", b"int x;\n");
+
+  let output =
+    slopcc().args(["-E", "-P", "-x", "c"]).arg(&input).output().expect("slopcc should run");
+  assert!(output.status.success());
+  assert_eq!(output.stdout, b"int x;\n");
+
+  let output = slopcc()
+    .arg("-E")
+    .arg(&input)
+    .args(["-x", "c"])
+    .output()
+    .expect("slopcc should run");
+  assert!(output.status.success());
+  assert!(output.stdout.is_empty(), "-x after the input must not apply to it");
+  let stderr = String::from_utf8_lossy(&output.stderr);
+  assert!(stderr.contains("linker input file unused"), "stderr: {stderr}");
+}
+
+#[test]
+fn forced_include_precedes_each_source() {
+  let dir = TempDir::new();
+  let config = dir.write("config.h", b"#define ANSWER 42\n");
+  let input = dir.write("foo.c", b"int x = ANSWER;\n");
+
+  let output = slopcc()
+    .args(["-E", "-P"])
+    .arg("-include")
+    .arg(&config)
+    .arg(&input)
+    .output()
+    .expect("slopcc should run");
+
+  assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+  assert_eq!(output.stdout, b"#define ANSWER 42\nint x = ANSWER;\n");
+}
+
+#[test]
+fn missing_forced_include_fails() {
+  let dir = TempDir::new();
+  let input = dir.write("foo.c", b"int x;\n");
+
+  let output = slopcc()
+    .args(["-E", "-include", "no-such-config.h"])
+    .arg(&input)
+    .output()
+    .expect("slopcc should run");
+
+  assert!(!output.status.success());
+  let stderr = String::from_utf8_lossy(&output.stderr);
+  assert!(stderr.contains("no-such-config.h"), "stderr: {stderr}");
+}
+
+#[test]
+fn missing_input_reports_path_and_exits_1() {
+  let dir = TempDir::new();
+  let missing = dir.path().join("missing.c");
+
+  let output = slopcc().arg("-E").arg(&missing).output().expect("slopcc should run");
+
+  assert_eq!(output.status.code(), Some(1));
+  let stderr = String::from_utf8_lossy(&output.stderr);
+  assert_eq!(stderr, format!("slopcc: {}: No such file or directory\n", missing.display()));
+}
+
+#[test]
+fn color_flag_controls_escape_codes() {
+  let dir = TempDir::new();
+  let input = dir.write("foo.c", b"/* a /* b */\n");
+
+  let output = slopcc()
+    .args(["-E", "-Wall", "--color=always"])
+    .arg(&input)
+    .output()
+    .expect("slopcc should run");
+  assert!(output.stderr.contains(&0x1b), "--color=always should emit escapes");
+
+  let output = slopcc()
+    .args(["-E", "-Wall", "--color=never"])
+    .arg(&input)
+    .output()
+    .expect("slopcc should run");
+  assert!(!output.stderr.is_empty());
+  assert!(!output.stderr.contains(&0x1b), "--color=never should stay plain");
+}
+
+#[test]
+fn output_creates_missing_parent_directories() {
+  let dir = TempDir::new();
+  let input = dir.write("foo.c", b"int x;\n");
+  let out = dir.path().join("build/sub/out.i");
+
+  let output = slopcc()
+    .args(["-E", "-P"])
+    .arg(&input)
+    .arg("-o")
+    .arg(&out)
+    .output()
+    .expect("slopcc should run");
+
+  assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+  assert_eq!(std::fs::read(&out).expect("output should exist"), b"int x;\n");
+  let leftovers: Vec<_> = std::fs::read_dir(out.parent().expect("output has a parent"))
+    .expect("output dir should be readable")
+    .map(|entry| entry.expect("entry should be readable").file_name())
+    .collect();
+  assert_eq!(leftovers, ["out.i"], "no temporary file should remain");
+}
+
+#[cfg(unix)]
+#[test]
+fn output_through_a_symlink_writes_its_target() {
+  let dir = TempDir::new();
+  let input = dir.write("foo.c", b"int x;\n");
+  let real = dir.write("real.i", b"");
+  let link = dir.path().join("link.i");
+  std::os::unix::fs::symlink(&real, &link).expect("symlink should be created");
+
+  let output = slopcc()
+    .args(["-E", "-P"])
+    .arg(&input)
+    .arg("-o")
+    .arg(&link)
+    .output()
+    .expect("slopcc should run");
+
+  assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+  let meta = std::fs::symlink_metadata(&link).expect("link should remain");
+  assert!(meta.file_type().is_symlink(), "-o must not replace the symlink");
+  assert_eq!(std::fs::read(&real).expect("target should be readable"), b"int x;\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn output_to_dev_null_leaves_the_device() {
+  let dir = TempDir::new();
+  let input = dir.write("foo.c", b"int x;\n");
+
+  let output = slopcc()
+    .args(["-E", "-P"])
+    .arg(&input)
+    .args(["-o", "/dev/null"])
+    .output()
+    .expect("slopcc should run");
+
+  assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+  let meta = std::fs::symlink_metadata("/dev/null").expect("/dev/null should still exist");
+  assert!(!meta.is_file(), "/dev/null must not become a regular file");
+}
+
+#[test]
+fn failed_preprocess_leaves_no_output_file() {
+  let dir = TempDir::new();
+  let input = dir.write("bad.c", b"int \0 x;\n");
+  let out = dir.path().join("build/out.i");
+
+  let output = slopcc()
+    .arg("-E")
+    .arg(&input)
+    .arg("-o")
+    .arg(&out)
+    .output()
+    .expect("slopcc should run");
+
+  assert!(!output.status.success());
+  assert!(!out.exists());
+}
+
+#[test]
+fn dump_macros_lists_command_line_defines() {
+  let dir = TempDir::new();
+  let input = dir.write("foo.c", b"#define LOCAL 2\nint x;\n");
+
+  let output = slopcc()
+    .args(["-E", "-D", "FOO=1", "-dM"])
+    .arg(&input)
+    .output()
+    .expect("slopcc should run");
+
+  assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+  let stdout = String::from_utf8_lossy(&output.stdout);
+  assert!(stdout.lines().any(|line| line == "#define FOO 1"), "stdout: {stdout}");
+  assert!(stdout.lines().all(|line| line.starts_with("#define ")), "stdout: {stdout}");
+}
+
+#[test]
+fn unknown_warning_option_is_ignored_with_a_warning() {
+  let dir = TempDir::new();
+  let input = dir.write("foo.c", b"int x;\n");
+
+  let output = slopcc().args(["-E", "-Wfoo"]).arg(&input).output().expect("slopcc should run");
+
+  assert!(output.status.success());
+  let stderr = String::from_utf8_lossy(&output.stderr);
+  assert!(
+    stderr.contains("warning: unrecognized command-line option '-Wfoo'; ignored"),
+    "stderr: {stderr}"
+  );
+}
+
+#[test]
+fn unknown_option_is_fatal() {
+  let dir = TempDir::new();
+  let input = dir.write("foo.c", b"int x;\n");
+
+  let output =
+    slopcc().args(["-E", "-funroll-loops"]).arg(&input).output().expect("slopcc should run");
+
+  assert_eq!(output.status.code(), Some(2));
+  let stderr = String::from_utf8_lossy(&output.stderr);
+  assert_eq!(stderr, "slopcc: unrecognized command-line option '-funroll-loops'\n");
+}
+
+#[test]
+fn pedantic_flags_report_c90_line_comments() {
+  let dir = TempDir::new();
+  let input = dir.write("foo.c", b"int x; // tail\n");
+
+  let output =
+    slopcc().args(["-E", "-std=c89"]).arg(&input).output().expect("slopcc should run");
+  assert!(output.status.success());
+  assert!(output.stderr.is_empty(), "pedantic warnings are off by default");
+
+  let output = slopcc()
+    .args(["-E", "-std=c89", "-pedantic"])
+    .arg(&input)
+    .output()
+    .expect("slopcc should run");
+  assert!(output.status.success());
+  let stderr = String::from_utf8_lossy(&output.stderr);
+  assert!(stderr.contains("warning: C++ style comments are not allowed"), "stderr: {stderr}");
+
+  let output = slopcc()
+    .args(["-E", "-std=c89", "-pedantic-errors"])
+    .arg(&input)
+    .output()
+    .expect("slopcc should run");
+  assert!(!output.status.success());
+  let stderr = String::from_utf8_lossy(&output.stderr);
+  assert!(stderr.contains("error: C++ style comments are not allowed"), "stderr: {stderr}");
+
+  let output = slopcc()
+    .args(["-E", "-pedantic-errors"])
+    .arg(&input)
+    .output()
+    .expect("slopcc should run");
+  assert!(output.status.success(), "// comments are standard C since C99");
+}
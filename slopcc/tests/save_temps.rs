@@ -0,0 +1,24 @@
+mod common;
+
+use common::{
+  slopcc,
+  TempDir,
+};
+
+#[test]
+fn save_temps_keeps_preprocessed_file() {
+  let dir = TempDir::new();
+  dir.write("foo.c", b"int x; /* gone */\n");
+
+  let output = slopcc()
+    .current_dir(dir.path())
+    .args(["-c", "-save-temps", "foo.c"])
+    .output()
+    .expect("slopcc should run");
+
+  // Code generation does not exist yet, so the run still fails after the
+  // preprocessed stage is written.
+  assert!(!output.status.success());
+  let kept = std::fs::read(dir.path().join("foo.i")).expect("foo.i should be kept");
+  assert_eq!(kept, b"# 1 \"foo.c\"\nint x;\n");
+}
@@ -0,0 +1,116 @@
+mod common;
+
+use common::{
+  slopcc,
+  TempDir,
+};
+
+#[test]
+fn clean_file_passes_and_writes_nothing() {
+  let dir = TempDir::new();
+  let input = dir.write("ok.c", b"int main(void) { return 0; }\n");
+
+  let output = slopcc()
+    .current_dir(dir.path())
+    .args(["-fsyntax-only", "ok.c"])
+    .output()
+    .expect("slopcc should run");
+
+  assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+  assert!(output.stdout.is_empty());
+  let entries: Vec<_> = std::fs::read_dir(dir.path())
+    .expect("scratch dir should be readable")
+    .map(|entry| entry.expect("entry should be readable").path())
+    .collect();
+  assert_eq!(entries, [input]);
+}
+
+#[test]
+fn lex_error_fails() {
+  let dir = TempDir::new();
+  let input = dir.write("bad.c", b"int \0 x;\n");
+
+  let output = slopcc().arg("-fsyntax-only").arg(&input).output().expect("slopcc should run");
+
+  assert!(!output.status.success());
+  let stderr = String::from_utf8_lossy(&output.stderr);
+  assert!(stderr.contains("null character in source"), "stderr: {stderr}");
+}
+
+#[test]
+fn lex_error_is_prefixed_with_its_location() {
+  let dir = TempDir::new();
+  dir.write("bad.c", b"int x;\n  @\n");
+
+  let output = slopcc()
+    .current_dir(dir.path())
+    .args(["-fsyntax-only", "bad.c"])
+    .output()
+    .expect("slopcc should run");
+
+  assert!(!output.status.success());
+  let stderr = String::from_utf8_lossy(&output.stderr);
+  assert!(
+    stderr.starts_with("bad.c:2:3: error: stray '@' in program\n    2 |   @\n      |   ^\n"),
+    "stderr: {stderr}"
+  );
+}
+
+#[test]
+fn diagnostics_end_with_a_summary() {
+  let dir = TempDir::new();
+  dir.write("bad.c", b"@ int c = u8'x';\n$@\n");
+
+  let output = slopcc()
+    .current_dir(dir.path())
+    .args(["-fsyntax-only", "-std=c17", "bad.c"])
+    .output()
+    .expect("slopcc should run");
+
+  assert_eq!(output.status.code(), Some(1));
+  let stderr = String::from_utf8_lossy(&output.stderr);
+  assert!(stderr.contains("\n3 errors, 1 warning generated\n"), "stderr: {stderr}");
+
+  let quiet = slopcc()
+    .current_dir(dir.path())
+    .args(["-fsyntax-only", "-std=c17", "-w", "bad.c"])
+    .output()
+    .expect("slopcc should run");
+
+  assert_eq!(quiet.status.code(), Some(1));
+  let stderr = String::from_utf8_lossy(&quiet.stderr);
+  assert!(!stderr.contains("generated"), "stderr: {stderr}");
+}
+
+#[test]
+fn clean_file_prints_no_summary() {
+  let dir = TempDir::new();
+  dir.write("ok.c", b"int x;\n");
+
+  let output = slopcc()
+    .current_dir(dir.path())
+    .args(["-fsyntax-only", "ok.c"])
+    .output()
+    .expect("slopcc should run");
+
+  assert!(output.status.success());
+  assert!(output.stderr.is_empty(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+}
+
+#[test]
+fn digit_separators_follow_the_standard() {
+  let dir = TempDir::new();
+  let input = dir.write("sep.c", b"int x = 1'000;\n");
+
+  for std in ["-std=gnu17", "-std=c23"] {
+    let output =
+      slopcc().args(["-fsyntax-only", std]).arg(&input).output().expect("slopcc should run");
+    assert!(output.status.success(), "{std}: {}", String::from_utf8_lossy(&output.stderr));
+  }
+
+  let output =
+    slopcc().args(["-fsyntax-only", "-std=c17"]).arg(&input).output().expect("slopcc should run");
+  assert!(!output.status.success());
+  let stderr = String::from_utf8_lossy(&output.stderr);
+  assert!(stderr.contains("missing terminating ' character"), "stderr: {stderr}");
+}
@@ -0,0 +1,50 @@
+mod common;
+
+use common::{
+  slopcc,
+  TempDir,
+};
+
+#[test]
+fn verbose_prints_banner_and_each_input() {
+  let dir = TempDir::new();
+  let first = dir.write("a.c", b"int a;\n");
+  let second = dir.write("b.c", b"int b;\n");
+
+  let output =
+    slopcc().args(["-E", "-P", "-v"]).arg(&first).arg(&second).output().expect("slopcc should run");
+
+  assert!(output.status.success());
+  assert_eq!(output.stdout, b"int a;\nint b;\n", "-v must not change the output");
+  let stderr = String::from_utf8_lossy(&output.stderr);
+  assert!(stderr.starts_with("slopcc version "), "stderr: {stderr}");
+  for input in [&first, &second] {
+    assert!(stderr.contains(&format!(" preprocessing {}\n", input.display())), "stderr: {stderr}");
+  }
+}
+
+#[test]
+fn verbose_lists_ignored_noop_flags() {
+  let dir = TempDir::new();
+  let input = dir.write("a.c", b"int a;\n");
+
+  let flags = ["-E", "-P", "-pipe", "-fPIC"];
+  let quiet = slopcc().args(flags).arg(&input).output().expect("slopcc should run");
+  assert!(quiet.status.success());
+  assert!(quiet.stderr.is_empty(), "stderr: {}", String::from_utf8_lossy(&quiet.stderr));
+
+  let verbose = slopcc().args(flags).arg("-v").arg(&input).output().expect("slopcc should run");
+  let stderr = String::from_utf8_lossy(&verbose.stderr);
+  assert!(stderr.contains(" ignoring -pipe\n ignoring -fPIC\n"), "stderr: {stderr}");
+}
+
+#[test]
+fn verbose_keeps_failure_exit_code() {
+  let dir = TempDir::new();
+  let input = dir.write("bad.c", b"int \0;\n");
+
+  let quiet = slopcc().arg("-E").arg(&input).output().expect("slopcc should run");
+  let verbose = slopcc().args(["-E", "-v"]).arg(&input).output().expect("slopcc should run");
+  assert_eq!(verbose.status.code(), quiet.status.code());
+  assert!(!verbose.status.success());
+}